@@ -0,0 +1,106 @@
+//! Sound effects for what happens in the game, and the heartbeat of the invaders' march
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{Bug, GameSound, MarchTempo},
+    settings::{EffectsVolume, MasterVolume, MusicVolume, MAX_VOLUME},
+    GameState,
+};
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_sounds)
+            .add_system(play_game_sounds)
+            .add_system_set(SystemSet::on_update(GameState::Game).with_system(march_music));
+    }
+}
+
+#[derive(Resource)]
+struct Sounds {
+    shot: Handle<AudioSource>,
+    explosion: Handle<AudioSource>,
+    bonus: Handle<AudioSource>,
+    player_hit: Handle<AudioSource>,
+    // The four descending notes of the march, played in turn
+    march: [Handle<AudioSource>; 4],
+}
+
+// Time between two notes of the march, at its slowest tempo
+const BEAT_SECONDS: f32 = 0.6;
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Sounds {
+        shot: asset_server.load("sounds/shot.wav"),
+        explosion: asset_server.load("sounds/explosion.wav"),
+        bonus: asset_server.load("sounds/bonus.wav"),
+        player_hit: asset_server.load("sounds/player_hit.wav"),
+        march: [1, 2, 3, 4].map(|note| asset_server.load(format!("sounds/march_{note}.wav"))),
+    });
+}
+
+// Volume of a channel, once scaled by the master volume
+fn gain(master: MasterVolume, channel: u32) -> f32 {
+    (*master * channel) as f32 / (MAX_VOLUME * MAX_VOLUME) as f32
+}
+
+fn play_game_sounds(
+    mut sound_events: EventReader<GameSound>,
+    sounds: Res<Sounds>,
+    audio: Res<Audio>,
+    master: Res<MasterVolume>,
+    effects: Res<EffectsVolume>,
+) {
+    let volume = gain(*master, **effects);
+    for sound in sound_events.iter() {
+        if volume == 0.0 {
+            continue;
+        }
+        let source = match sound {
+            GameSound::Shot => &sounds.shot,
+            GameSound::Explosion => &sounds.explosion,
+            GameSound::Bonus => &sounds.bonus,
+            GameSound::PlayerHit => &sounds.player_hit,
+        };
+        audio.play_with_settings(source.clone(), PlaybackSettings::ONCE.with_volume(volume));
+    }
+}
+
+#[derive(Default)]
+struct MarchBeat {
+    elapsed: f32,
+    note: usize,
+}
+
+// Plays the next note of the march every beat, the beats get closer as the march speeds up
+#[allow(clippy::too_many_arguments)]
+fn march_music(
+    time: Res<Time>,
+    tempo: Res<MarchTempo>,
+    sounds: Res<Sounds>,
+    audio: Res<Audio>,
+    master: Res<MasterVolume>,
+    music: Res<MusicVolume>,
+    bug_query: Query<(), With<Bug>>,
+    mut beat: Local<MarchBeat>,
+) {
+    // Silence between two waves
+    if bug_query.is_empty() {
+        return;
+    }
+
+    beat.elapsed += time.delta_seconds() * **tempo;
+    if beat.elapsed < BEAT_SECONDS {
+        return;
+    }
+    beat.elapsed = 0.0;
+
+    let volume = gain(*master, **music);
+    if volume > 0.0 {
+        let note = sounds.march[beat.note].clone();
+        audio.play_with_settings(note, PlaybackSettings::ONCE.with_volume(volume));
+    }
+    beat.note = (beat.note + 1) % sounds.march.len();
+}
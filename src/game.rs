@@ -0,0 +1,1749 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{despawn_screen, replay::Playback, GameState, TEXT_COLOR, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+#[derive(Default)]
+pub struct GamePlugin {
+    // Seed of the first run, a random one is picked when it isn't set
+    pub seed: Option<u64>,
+}
+
+// Stage running the gameplay simulation, once per tick of the `SimulationClock`
+#[derive(StageLabel)]
+pub struct FixedUpdateStage;
+
+// The system moving the ship, anything feeding `PlayerInput` during a tick runs before it
+#[derive(SystemLabel)]
+pub struct PlayerSystem;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+
+        app.add_event::<LifeLost>()
+            .add_event::<Collision>()
+            .add_event::<GameSound>()
+            .insert_resource(RunSeed(seed))
+            .insert_resource(GameRng::from_seed(seed))
+            .init_resource::<SimulationClock>()
+            // Only there when running without the `InputPlugin`, i.e. headless
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<Controls>()
+            .init_resource::<EnemyFire>()
+            .init_resource::<Difficulty>()
+            .init_resource::<TempoCurve>()
+            .init_resource::<MarchTempo>()
+            .add_stage_after(
+                CoreStage::Update,
+                FixedUpdateStage,
+                SystemStage::parallel().with_run_criteria(simulation_tick),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(game_setup))
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(pause)
+                    .with_system(read_player_input)
+                    .with_system(power_up_board)
+                    .with_system(scoreboard)
+                    .with_system(lives_board)
+                    .with_system(wave_board),
+            )
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::new()
+                    .with_system(player.label(PlayerSystem))
+                    .with_system(march_tempo)
+                    .with_system(bug_movement.after(march_tempo))
+                    .with_system(laser_movement)
+                    // Systems drawing from the `GameRng` run in a fixed order, so a seed
+                    // always gives the same run
+                    .with_system(mothership_spawner)
+                    .with_system(mothership_movement)
+                    .with_system(bug_fire.after(mothership_spawner).after(bug_movement))
+                    .with_system(bug_laser_movement)
+                    .with_system(power_up_movement)
+                    .with_system(
+                        detect_collisions
+                            .after(bug_fire)
+                            .after(player)
+                            .after(bug_movement)
+                            .after(laser_movement)
+                            .after(mothership_movement)
+                            .after(bug_laser_movement)
+                            .after(power_up_movement),
+                    )
+                    .with_system(bug_zapper.after(detect_collisions))
+                    .with_system(mothership_zapper.after(bug_zapper))
+                    .with_system(player_hit.after(detect_collisions))
+                    .with_system(shield_damage.after(detect_collisions))
+                    .with_system(power_up_pickup.after(detect_collisions))
+                    // Same for the systems where one reads what another one changes, or a
+                    // replay could see them the other way around
+                    .with_system(power_up_timer.after(player).after(player_hit))
+                    .with_system(bug_landing.after(bug_zapper).after(player_hit))
+                    .with_system(
+                        lose_life
+                            .after(bug_landing)
+                            .after(player_hit)
+                            .after(mothership_zapper),
+                    )
+                    .with_system(next_wave.after(lose_life)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Game)
+                    .with_system(despawn_screen::<OnGameScreen>)
+                    .with_system(next_run_seed),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OnGameScreen;
+
+// Seed of the current run, every random decision of the run follows from it
+#[derive(Resource, Deref, DerefMut, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunSeed(pub u64);
+
+// The only source of randomness for the gameplay, so a run can be reproduced from its seed
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng(StdRng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+// Longest stretch of real time simulated in a single frame, so a long hitch doesn't turn into
+// a burst of catch-up ticks
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+// Drives the gameplay at a fixed rate so it plays the same whatever the display refresh rate.
+// In manual mode the real time is ignored and only the queued ticks are simulated
+#[derive(Resource)]
+pub struct SimulationClock {
+    step: Duration,
+    accumulator: Duration,
+    manual: bool,
+    ticks: u64,
+}
+
+impl SimulationClock {
+    pub fn new(ticks_per_second: u32) -> Self {
+        Self {
+            step: Duration::from_secs(1) / ticks_per_second,
+            accumulator: Duration::ZERO,
+            manual: false,
+            ticks: 0,
+        }
+    }
+
+    pub fn manual(ticks_per_second: u32) -> Self {
+        Self {
+            manual: true,
+            ..Self::new(ticks_per_second)
+        }
+    }
+
+    // Simulates `ticks` more ticks, over the next updates
+    pub fn queue_ticks(&mut self, ticks: u32) {
+        self.accumulator += self.step * ticks;
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub fn step_seconds(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    // Number of ticks simulated since the clock was created
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+fn simulation_tick(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut clock: ResMut<SimulationClock>,
+    mut looping: Local<bool>,
+) -> ShouldRun {
+    // The stage has no state driver of its own, it follows the state settled during `Update`.
+    // The clock stands still meanwhile, so a pause doesn't pile up ticks
+    if *state.current() != GameState::Game {
+        *looping = false;
+        return ShouldRun::No;
+    }
+
+    // Real time is only added once per frame, then the stage loops until it is used up
+    if !*looping && !clock.manual {
+        clock.accumulator = (clock.accumulator + time.delta()).min(MAX_FRAME_TIME);
+    }
+
+    let step = clock.step;
+    if clock.accumulator >= step {
+        clock.accumulator -= step;
+        clock.ticks += 1;
+        *looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *looping = false;
+        ShouldRun::No
+    }
+}
+
+// Player controls sampled every frame, for the simulation ticks to consume
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerInput {
+    pub left: bool,
+    pub right: bool,
+    // Latched until a tick consumes it, so a press is never lost between ticks
+    pub fire: bool,
+    pub fire_held: bool,
+}
+
+// Something the player does with a key of their choice
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlAction {
+    Left,
+    Right,
+    Fire,
+}
+
+impl ControlAction {
+    pub const ALL: [ControlAction; 3] = [
+        ControlAction::Left,
+        ControlAction::Right,
+        ControlAction::Fire,
+    ];
+}
+
+// Key bound to each of the player's actions
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Controls {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub fire: KeyCode,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            fire: KeyCode::Space,
+        }
+    }
+}
+
+impl Controls {
+    pub fn key(&self, action: ControlAction) -> KeyCode {
+        match action {
+            ControlAction::Left => self.left,
+            ControlAction::Right => self.right,
+            ControlAction::Fire => self.fire,
+        }
+    }
+
+    // Binds `key` to `action`, unless another action already uses it, which is returned instead
+    pub fn bind(&mut self, action: ControlAction, key: KeyCode) -> Result<(), ControlAction> {
+        if let Some(other) = self.action(key).filter(|other| *other != action) {
+            return Err(other);
+        }
+        match action {
+            ControlAction::Left => self.left = key,
+            ControlAction::Right => self.right = key,
+            ControlAction::Fire => self.fire = key,
+        }
+        Ok(())
+    }
+
+    // The action bound to `key`, if any
+    pub fn action(&self, key: KeyCode) -> Option<ControlAction> {
+        ControlAction::ALL
+            .into_iter()
+            .find(|action| self.key(*action) == key)
+    }
+
+    // Whether two actions share a key, which only a hand edited settings file can do
+    pub fn has_conflicts(&self) -> bool {
+        ControlAction::ALL
+            .into_iter()
+            .any(|action| self.action(self.key(action)) != Some(action))
+    }
+}
+
+// Points scored during the current game, reset every time a game starts
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct Score(pub u32);
+
+// Tag component used to find the text that displays the score
+#[derive(Component)]
+struct ScoreText;
+
+// Groups of colliders, only some pairs of layers can collide with each other
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CollisionLayer {
+    PlayerShot,
+    EnemyShot,
+    Enemy,
+    Player,
+    Shield,
+    PowerUp,
+}
+
+impl CollisionLayer {
+    const COUNT: usize = 6;
+
+    fn is_shot(&self) -> bool {
+        matches!(self, CollisionLayer::PlayerShot | CollisionLayer::EnemyShot)
+    }
+}
+
+// Pairs of layers that collide, collision events always list the entities in this order
+const COLLIDING_LAYERS: [(CollisionLayer, CollisionLayer); 7] = [
+    (CollisionLayer::PlayerShot, CollisionLayer::Enemy),
+    (CollisionLayer::PlayerShot, CollisionLayer::Shield),
+    (CollisionLayer::EnemyShot, CollisionLayer::Player),
+    (CollisionLayer::EnemyShot, CollisionLayer::Shield),
+    (CollisionLayer::Enemy, CollisionLayer::Player),
+    (CollisionLayer::Enemy, CollisionLayer::Shield),
+    (CollisionLayer::PowerUp, CollisionLayer::Player),
+];
+
+// Axis aligned box centred on the entity's translation
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Collider {
+    pub layer: CollisionLayer,
+    pub size: Vec2,
+}
+
+impl Collider {
+    pub fn new(layer: CollisionLayer, size: Vec2) -> Self {
+        Self { layer, size }
+    }
+
+    fn overlaps(&self, position: Vec3, other: &Collider, other_position: Vec3) -> bool {
+        let distance = (position - other_position).truncate().abs();
+        distance.cmplt((self.size + other.size) / 2.0).all()
+    }
+}
+
+// Sent for every pair of overlapping colliders whose layers collide
+pub struct Collision {
+    pub entities: (Entity, Entity),
+    pub layers: (CollisionLayer, CollisionLayer),
+}
+
+impl Collision {
+    // The entities involved, if this collision happened between these two layers
+    pub fn between(
+        &self,
+        first: CollisionLayer,
+        second: CollisionLayer,
+    ) -> Option<(Entity, Entity)> {
+        (self.layers == (first, second)).then_some(self.entities)
+    }
+}
+
+// A collision along with where its two colliders were
+struct Hit {
+    collision: Collision,
+    positions: [Vec3; 2],
+}
+
+impl Hit {
+    fn distance_squared(&self) -> f32 {
+        self.positions[0].distance_squared(self.positions[1])
+    }
+
+    // Entity ids depend on everything spawned before the run, a live run and its replay only
+    // agree on the layers and positions
+    fn stable_cmp(&self, other: &Hit) -> std::cmp::Ordering {
+        let coordinates = |hit: &Hit| hit.positions.map(|position| position.to_array()).concat();
+        self.collision
+            .layers
+            .cmp(&other.collision.layers)
+            .then_with(|| {
+                coordinates(self)
+                    .iter()
+                    .zip(coordinates(other))
+                    .map(|(a, b)| a.total_cmp(&b))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+}
+
+const STARTING_LIVES: u32 = 3;
+
+// Lives left before the round is over
+#[derive(Resource, Deref, DerefMut)]
+pub struct Lives(pub u32);
+
+// Tag component used to find the text that displays the remaining lives
+#[derive(Component)]
+struct LivesText;
+
+// The wave the player is currently fighting, starting at 1
+#[derive(Resource, Deref, DerefMut)]
+pub struct Wave(pub u32);
+
+impl Wave {
+    // How far below the starting line this wave's formation is spawned
+    fn start_drop(&self) -> f32 {
+        (self.0 - 1).min(MAX_WAVE_DROPS) as f32 * WAVE_DROP
+    }
+
+    // Horizontal speed of the bugs of this wave, in units per second
+    fn march_speed(&self) -> f32 {
+        BUG_SPEED * (1.0 + WAVE_SPEED_STEP * (self.0 - 1).min(MAX_WAVE_SPEED_STEPS) as f32)
+    }
+}
+
+const BUG_SPEED: f32 = 120.0;
+const WAVE_DROP: f32 = 20.0;
+const MAX_WAVE_DROPS: u32 = 6;
+const WAVE_SPEED_STEP: f32 = 0.25;
+const MAX_WAVE_SPEED_STEPS: u32 = 8;
+const WAVE_INTERLUDE_SECONDS: f32 = 2.0;
+
+// Counts down the pause between a cleared wave and the next one
+#[derive(Resource, Deref, DerefMut)]
+struct WaveInterlude(Timer);
+
+// Tag component used to find the text that displays the current wave
+#[derive(Component)]
+struct WaveText;
+
+// What cost the player a life
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    Invaded,
+    ShotDown,
+}
+
+// Sent every time the player loses a life
+struct LifeLost(DeathCause);
+
+// Something that happened in the game and deserves a sound
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameSound {
+    Shot,
+    Explosion,
+    Bonus,
+    PlayerHit,
+}
+
+// Summary of a finished run, kept around for the game over screen
+#[derive(Resource)]
+pub struct RunResult {
+    pub score: u32,
+    pub wave: u32,
+    pub cause: DeathCause,
+}
+
+// Texture atlas shared by every sprite spawned during the game
+#[derive(Resource, Deref)]
+struct SpriteSheet(Handle<TextureAtlas>);
+
+#[derive(Component)]
+pub struct Player {
+    pub delta_x: f32,
+}
+
+// Limits how fast a ship can fire
+#[derive(Component)]
+pub struct Weapon {
+    pub cooldown: Timer,
+    // How many of this ship's lasers can be on screen at the same time
+    pub max_lasers: usize,
+}
+
+impl Weapon {
+    pub fn new(cooldown_seconds: f32, max_lasers: usize) -> Self {
+        Self {
+            cooldown: Timer::from_seconds(cooldown_seconds, TimerMode::Once),
+            max_lasers,
+        }
+    }
+}
+
+impl Default for Weapon {
+    // The classic single shot: a new laser only once the previous one is gone
+    fn default() -> Self {
+        Self::new(0.1, 1)
+    }
+}
+
+#[derive(
+    Resource, Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    // Weapon the player's ship starts with
+    pub fn weapon(&self) -> Weapon {
+        match self {
+            Difficulty::Easy => Weapon::new(0.2, 2),
+            Difficulty::Normal => Weapon::default(),
+            Difficulty::Hard => Weapon::new(0.4, 1),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum BugMovement {
+    Left,
+    Right,
+    // `n` is the distance left to drop before marching sideways again
+    Down { n: f32, next_left: bool },
+}
+
+const MARCH_DROP: f32 = 24.0;
+const DROP_SPEED: f32 = 120.0;
+
+// Drives the whole grid of bugs so they march in lockstep
+#[derive(Resource)]
+struct Formation {
+    movement: BugMovement,
+    // How many bugs the formation started with
+    size: usize,
+}
+
+// How the march speeds up as the formation thins out. The tempo goes from 1.0 with a full
+// formation up to `max_tempo` for the last bug standing, `exponent` shapes the curve in between
+#[derive(Resource)]
+pub struct TempoCurve {
+    pub max_tempo: f32,
+    pub exponent: f32,
+}
+
+impl Default for TempoCurve {
+    fn default() -> Self {
+        Self {
+            max_tempo: 6.0,
+            exponent: 3.0,
+        }
+    }
+}
+
+impl TempoCurve {
+    pub fn tempo(&self, alive: usize, size: usize) -> f32 {
+        if size == 0 {
+            return 1.0;
+        }
+        let killed = 1.0 - alive as f32 / size as f32;
+        1.0 + (self.max_tempo - 1.0) * killed.clamp(0.0, 1.0).powf(self.exponent)
+    }
+}
+
+// Current speed multiplier of the march, for anything that needs to keep up with the bugs
+#[derive(Resource, Deref)]
+pub struct MarchTempo(pub f32);
+
+impl Default for MarchTempo {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+// The different invaders, from the back rows to the front ones
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BugKind {
+    Squid,
+    Crab,
+    Octopus,
+}
+
+impl BugKind {
+    // Classic layout: squids in the back row, crabs in the middle and octopuses in front
+    fn for_row(row: u32) -> Self {
+        match row {
+            0 => BugKind::Squid,
+            1 => BugKind::Crab,
+            _ => BugKind::Octopus,
+        }
+    }
+
+    fn sprite_index(&self) -> usize {
+        match self {
+            BugKind::Squid => 4,
+            BugKind::Crab => 1,
+            BugKind::Octopus => 5,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        match self {
+            BugKind::Squid => 30,
+            BugKind::Crab => 20,
+            BugKind::Octopus => 10,
+        }
+    }
+
+    pub fn hit_points(&self) -> u32 {
+        match self {
+            BugKind::Squid => 2,
+            BugKind::Crab | BugKind::Octopus => 1,
+        }
+    }
+
+    // How likely this kind is to be picked when the formation fires, relative to the others
+    fn fire_weight(&self) -> f32 {
+        match self {
+            BugKind::Squid => 2.0,
+            BugKind::Crab => 1.0,
+            BugKind::Octopus => 0.5,
+        }
+    }
+
+    // How fast the shots fired by this kind fall, in units per second
+    fn shot_speed(&self) -> f32 {
+        match self {
+            BugKind::Squid => 360.0,
+            BugKind::Crab | BugKind::Octopus => 240.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Bug {
+    pub kind: BugKind,
+    pub health: u32,
+    pub column: u32,
+}
+
+// Laser sprites draw two prongs at the edges of their cell, shots hit whatever is between them
+pub const SHOT_SIZE: Vec2 = Vec2::new(22.0, 24.0);
+
+#[derive(Component)]
+pub struct Laser {
+    // The ship that fired it
+    pub owner: Entity,
+    // Sideways speed, used by the spread shot
+    pub drift: f32,
+    // Piercing lasers keep going through the bugs they hit
+    pub piercing: bool,
+    // Bugs a piercing laser already went through, each of them only takes one hit from it
+    pub pierced: HashSet<Entity>,
+}
+
+impl Laser {
+    pub fn new(owner: Entity) -> Self {
+        Self {
+            owner,
+            drift: 0.0,
+            piercing: false,
+            pierced: HashSet::new(),
+        }
+    }
+}
+
+// Bonus dropped by destroyed bugs, changes how the ship fires for a while
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUpKind {
+    SpreadShot,
+    RapidFire,
+    PiercingLaser,
+    Shield,
+}
+
+impl PowerUpKind {
+    const ALL: [PowerUpKind; 4] = [
+        PowerUpKind::SpreadShot,
+        PowerUpKind::RapidFire,
+        PowerUpKind::PiercingLaser,
+        PowerUpKind::Shield,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            PowerUpKind::SpreadShot => "SPREAD",
+            PowerUpKind::RapidFire => "RAPID",
+            PowerUpKind::PiercingLaser => "PIERCE",
+            PowerUpKind::Shield => "SHIELD",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUpKind::SpreadShot => Color::YELLOW,
+            PowerUpKind::RapidFire => Color::ORANGE,
+            PowerUpKind::PiercingLaser => Color::FUCHSIA,
+            PowerUpKind::Shield => Color::CYAN,
+        }
+    }
+}
+
+const POWER_UP_DROP_CHANCE: f64 = 0.05;
+const POWER_UP_FALL_SPEED: f32 = 120.0;
+const POWER_UP_SECONDS: f32 = 10.0;
+const SPREAD_SHOT_DRIFT: f32 = 60.0;
+const RAPID_FIRE_COOLDOWN: f32 = 0.08;
+const RAPID_FIRE_MAX_LASERS: usize = 6;
+
+// Capsule falling towards the player, picked up on contact
+#[derive(Component)]
+pub struct PowerUpCapsule(pub PowerUpKind);
+
+// Power-up currently held by the player, until its timer runs out
+#[derive(Component)]
+pub struct ActivePowerUp {
+    pub kind: PowerUpKind,
+    pub timer: Timer,
+}
+
+// Tag component used to find the text that displays the active power-up
+#[derive(Component)]
+struct PowerUpText;
+
+// Projectile fired downwards by the bugs
+#[derive(Component)]
+pub struct BugLaser {
+    pub speed: f32,
+}
+
+// Bonus target that crosses the top of the screen every now and then
+#[derive(Component)]
+pub struct Mothership {
+    pub direction: f32,
+}
+
+// Counts down until the next mothership shows up
+#[derive(Resource, Deref, DerefMut)]
+struct MothershipTimer(Timer);
+
+const MOTHERSHIP_SPEED: f32 = 90.0;
+const MOTHERSHIP_INTERVAL_SECONDS: (f32, f32) = (15.0, 30.0);
+const MOTHERSHIP_POINTS: [u32; 5] = [50, 100, 150, 200, 300];
+
+impl MothershipTimer {
+    fn random(rng: &mut impl Rng) -> Self {
+        let (min, max) = MOTHERSHIP_INTERVAL_SECONDS;
+        let seconds = rng.gen_range(min..max);
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+// A small piece of a bunker, knocked out by anything that touches it
+#[derive(Component)]
+pub struct ShieldCell;
+
+const SHIELD_CELL_SIZE: f32 = 6.0;
+const SHIELD_COUNT: usize = 4;
+const SHIELD_SHAPE: [&str; 6] = [
+    "  ######  ",
+    " ######## ",
+    "##########",
+    "##########",
+    "###    ###",
+    "##      ##",
+];
+
+// How often the bugs shoot back, on average, across the whole formation
+#[derive(Resource)]
+pub struct EnemyFire {
+    pub shots_per_second: f32,
+}
+
+impl Default for EnemyFire {
+    fn default() -> Self {
+        Self {
+            shots_per_second: 0.8,
+        }
+    }
+}
+
+fn pause(mut keyboard_input: ResMut<Input<KeyCode>>, mut game_state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        // Pushing keeps the game underneath alive, frozen until it is popped back
+        let _ = game_state.push(GameState::Paused);
+        // Otherwise the pause menu would see the same press and resume straight away
+        keyboard_input.clear_just_pressed(KeyCode::Escape);
+    }
+}
+
+fn read_player_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    controls: Res<Controls>,
+    mut input: ResMut<PlayerInput>,
+) {
+    input.left = keyboard_input.pressed(controls.left);
+    input.right = keyboard_input.pressed(controls.right);
+    input.fire |= keyboard_input.just_pressed(controls.fire);
+    input.fire_held = keyboard_input.pressed(controls.fire);
+}
+
+#[allow(clippy::type_complexity)]
+fn player(
+    mut input: ResMut<PlayerInput>,
+    clock: Res<SimulationClock>,
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Player,
+        &mut Weapon,
+        &mut Transform,
+        &Handle<TextureAtlas>,
+        Option<&ActivePowerUp>,
+    )>,
+    laser_query: Query<&Laser>,
+    mut sounds: EventWriter<GameSound>,
+) {
+    const ACCELERATION: f32 = 3600.0;
+    const MAX_VELOCITY: f32 = 960.0;
+    // Exponential decay rate of the velocity, in 1/s
+    const FRICTION: f32 = 17.26;
+
+    let dt = clock.step_seconds();
+
+    for (entity, mut player, mut weapon, mut trans, atlas_handle, power_up) in query.iter_mut() {
+        let mut firing = false;
+        weapon.cooldown.tick(clock.step());
+
+        let power_up = power_up.map(|power_up| power_up.kind);
+        let (trigger, ready, max_lasers) = match power_up {
+            // Holding the trigger keeps firing, as fast as the quicker of the two cooldowns
+            Some(PowerUpKind::RapidFire) => (
+                input.fire_held,
+                weapon.cooldown.finished() || weapon.cooldown.elapsed_secs() >= RAPID_FIRE_COOLDOWN,
+                weapon.max_lasers.max(RAPID_FIRE_MAX_LASERS),
+            ),
+            // Every volley is three lasers wide
+            Some(PowerUpKind::SpreadShot) => (
+                input.fire,
+                weapon.cooldown.finished(),
+                weapon.max_lasers * 3,
+            ),
+            _ => (input.fire, weapon.cooldown.finished(), weapon.max_lasers),
+        };
+
+        if input.left {
+            player.delta_x -= ACCELERATION * dt;
+        }
+        if input.right {
+            player.delta_x += ACCELERATION * dt;
+        }
+        let lasers = laser_query
+            .iter()
+            .filter(|laser| laser.owner == entity)
+            .count();
+        if trigger && ready && lasers < max_lasers {
+            firing = true;
+            weapon.cooldown.reset();
+        }
+
+        // Apply movement deltas
+        player.delta_x = player.delta_x.clamp(-MAX_VELOCITY, MAX_VELOCITY);
+        trans.translation.x += player.delta_x * dt;
+        trans.translation.x = trans
+            .translation
+            .x
+            .clamp(-(WINDOW_WIDTH / 2.5), WINDOW_WIDTH / 2.5);
+
+        // Decelerate
+        player.delta_x *= (-FRICTION * dt).exp();
+
+        if firing {
+            sounds.send(GameSound::Shot);
+            let lasers = match power_up {
+                Some(PowerUpKind::SpreadShot) => vec![
+                    Laser {
+                        drift: -SPREAD_SHOT_DRIFT,
+                        ..Laser::new(entity)
+                    },
+                    Laser::new(entity),
+                    Laser {
+                        drift: SPREAD_SHOT_DRIFT,
+                        ..Laser::new(entity)
+                    },
+                ],
+                Some(PowerUpKind::PiercingLaser) => vec![Laser {
+                    piercing: true,
+                    ..Laser::new(entity)
+                }],
+                _ => vec![Laser::new(entity)],
+            };
+
+            for laser in lasers {
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: atlas_handle.clone(),
+                        transform: Transform::from_translation(Vec3::new(
+                            trans.translation.x,
+                            trans.translation.y + 24.0,
+                            0.0,
+                        )),
+                        sprite: TextureAtlasSprite {
+                            index: 2,
+                            color: power_up.map_or(Color::LIME_GREEN, |power_up| power_up.color()),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    Collider::new(CollisionLayer::PlayerShot, SHOT_SIZE),
+                    laser,
+                    OnGameScreen,
+                ));
+            }
+        }
+    }
+
+    // The press has been handled, whether it fired or not
+    input.fire = false;
+}
+
+fn march_tempo(
+    curve: Res<TempoCurve>,
+    formation: Res<Formation>,
+    bug_query: Query<(), With<Bug>>,
+    mut tempo: ResMut<MarchTempo>,
+) {
+    let current = curve.tempo(bug_query.iter().count(), formation.size);
+    if **tempo != current {
+        tempo.0 = current;
+    }
+}
+
+fn bug_movement(
+    clock: Res<SimulationClock>,
+    wave: Res<Wave>,
+    tempo: Res<MarchTempo>,
+    mut formation: ResMut<Formation>,
+    mut query: Query<&mut Transform, With<Bug>>,
+) {
+    if query.is_empty() {
+        return;
+    }
+
+    // Only the bugs still alive count, so the march keeps going once the edge columns are gone
+    let (left, right) = query
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(left, right), trans| {
+            (
+                left.min(trans.translation.x),
+                right.max(trans.translation.x),
+            )
+        });
+    let speed = wave.march_speed() * **tempo * clock.step_seconds();
+
+    let step = match formation.movement {
+        BugMovement::Left => {
+            if left - speed < -(WINDOW_WIDTH / 2.5) {
+                formation.movement = BugMovement::Down {
+                    n: MARCH_DROP,
+                    next_left: false,
+                };
+            }
+            Vec3::new(-speed, 0.0, 0.0)
+        }
+        BugMovement::Right => {
+            if right + speed > WINDOW_WIDTH / 2.5 {
+                formation.movement = BugMovement::Down {
+                    n: MARCH_DROP,
+                    next_left: true,
+                };
+            }
+            Vec3::new(speed, 0.0, 0.0)
+        }
+        BugMovement::Down { n, next_left } => {
+            let drop = (DROP_SPEED * **tempo * clock.step_seconds()).min(n);
+            formation.movement = if n - drop <= 0.0 {
+                if next_left {
+                    BugMovement::Left
+                } else {
+                    BugMovement::Right
+                }
+            } else {
+                BugMovement::Down {
+                    n: n - drop,
+                    next_left,
+                }
+            };
+            Vec3::new(0.0, -drop, 0.0)
+        }
+    };
+
+    for mut trans in query.iter_mut() {
+        trans.translation += step;
+    }
+}
+
+fn laser_movement(
+    clock: Res<SimulationClock>,
+    mut query: Query<(Entity, &Laser, &mut Transform)>,
+    mut commands: Commands,
+) {
+    const SPEED: f32 = 240.0;
+
+    for (entity, laser, mut trans) in query.iter_mut() {
+        trans.translation += Vec3::new(laser.drift, SPEED, 0.0) * clock.step_seconds();
+
+        if trans.translation.y > WINDOW_HEIGHT / 2.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn bug_fire(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    mut rng: ResMut<GameRng>,
+    enemy_fire: Res<EnemyFire>,
+    sprite_sheet: Res<SpriteSheet>,
+    bug_query: Query<(&Bug, &Transform)>,
+) {
+    if rng.gen::<f32>() >= enemy_fire.shots_per_second * clock.step_seconds() {
+        return;
+    }
+
+    // Only the lowest bug of each column shoots, otherwise it would hit the bugs below it
+    let mut shooters: BTreeMap<u32, (Vec3, BugKind)> = BTreeMap::new();
+    for (bug, trans) in bug_query.iter() {
+        shooters
+            .entry(bug.column)
+            .and_modify(|lowest| {
+                if trans.translation.y < lowest.0.y {
+                    *lowest = (trans.translation, bug.kind);
+                }
+            })
+            .or_insert((trans.translation, bug.kind));
+    }
+    let shooters: Vec<_> = shooters.into_values().collect();
+    let Ok((position, kind)) = shooters.choose_weighted(&mut **rng, |(_, kind)| kind.fire_weight())
+    else {
+        return;
+    };
+
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: sprite_sheet.clone(),
+            transform: Transform::from_translation(Vec3::new(position.x, position.y - 24.0, 0.0)),
+            sprite: TextureAtlasSprite {
+                index: 2,
+                color: Color::ORANGE_RED,
+                flip_y: true,
+                ..default()
+            },
+            ..default()
+        },
+        BugLaser {
+            speed: kind.shot_speed(),
+        },
+        Collider::new(CollisionLayer::EnemyShot, SHOT_SIZE),
+        OnGameScreen,
+    ));
+}
+
+fn bug_laser_movement(
+    clock: Res<SimulationClock>,
+    mut query: Query<(Entity, &BugLaser, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, bug_laser, mut trans) in query.iter_mut() {
+        trans.translation.y -= bug_laser.speed * clock.step_seconds();
+
+        if trans.translation.y < -(WINDOW_HEIGHT / 2.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn detect_collisions(
+    query: Query<(Entity, &Collider, &Transform, Option<&Laser>)>,
+    mut collisions: EventWriter<Collision>,
+) {
+    // Colliders sorted by layer, so only the layers that can collide get compared
+    let mut layers: [Vec<_>; CollisionLayer::COUNT] = Default::default();
+    for collider in &query {
+        layers[collider.1.layer as usize].push(collider);
+    }
+
+    let mut hits = Vec::new();
+    // Closest hit of every non-piercing shot, so a shot is never consumed twice
+    let mut shot_hits: BTreeMap<Entity, Hit> = BTreeMap::new();
+
+    let pairs = COLLIDING_LAYERS
+        .iter()
+        .flat_map(|(first_layer, second_layer)| {
+            let seconds = &layers[*second_layer as usize];
+            layers[*first_layer as usize]
+                .iter()
+                .flat_map(move |first| seconds.iter().map(move |second| (first, second)))
+        });
+    for (first, second) in pairs {
+        let (first_entity, first_collider, first_transform, first_laser) = *first;
+        let (second_entity, second_collider, second_transform, _) = *second;
+
+        if !first_collider.overlaps(
+            first_transform.translation,
+            second_collider,
+            second_transform.translation,
+        ) {
+            continue;
+        }
+
+        let hit = Hit {
+            collision: Collision {
+                entities: (first_entity, second_entity),
+                layers: (first_collider.layer, second_collider.layer),
+            },
+            positions: [first_transform.translation, second_transform.translation],
+        };
+        let piercing = first_laser.is_some_and(|laser| laser.piercing);
+        if first_collider.layer.is_shot() && !piercing {
+            let closer = |closest: &Hit| {
+                hit.distance_squared()
+                    .total_cmp(&closest.distance_squared())
+                    .then_with(|| hit.stable_cmp(closest))
+                    .is_lt()
+            };
+            if shot_hits.get(&first_entity).is_none_or(closer) {
+                shot_hits.insert(first_entity, hit);
+            }
+        } else {
+            hits.push(hit);
+        }
+    }
+
+    // The systems handling collisions draw from the `GameRng`, so they must see them in the
+    // same order when the run is replayed
+    hits.extend(shot_hits.into_values());
+    hits.sort_by(Hit::stable_cmp);
+    collisions.send_batch(hits.into_iter().map(|hit| hit.collision));
+}
+
+fn player_hit(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    player_query: Query<Option<&ActivePowerUp>, With<Player>>,
+    mut life_lost_events: EventWriter<LifeLost>,
+) {
+    for collision in collisions.iter() {
+        let Some((shot, player)) =
+            collision.between(CollisionLayer::EnemyShot, CollisionLayer::Player)
+        else {
+            continue;
+        };
+
+        commands.entity(shot).despawn();
+        let shielded = matches!(
+            player_query.get(player),
+            Ok(Some(ActivePowerUp {
+                kind: PowerUpKind::Shield,
+                ..
+            }))
+        );
+        if !shielded {
+            life_lost_events.send(LifeLost(DeathCause::ShotDown));
+        }
+    }
+}
+
+fn power_up_movement(
+    clock: Res<SimulationClock>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform), With<PowerUpCapsule>>,
+) {
+    for (entity, mut trans) in query.iter_mut() {
+        trans.translation.y -= POWER_UP_FALL_SPEED * clock.step_seconds();
+
+        if trans.translation.y < -(WINDOW_HEIGHT / 2.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn power_up_pickup(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    capsule_query: Query<&PowerUpCapsule>,
+) {
+    for collision in collisions.iter() {
+        let Some((capsule, player)) =
+            collision.between(CollisionLayer::PowerUp, CollisionLayer::Player)
+        else {
+            continue;
+        };
+        let Ok(PowerUpCapsule(kind)) = capsule_query.get(capsule) else {
+            continue;
+        };
+
+        // A new power-up replaces the current one and restarts the clock
+        commands.entity(player).insert(ActivePowerUp {
+            kind: *kind,
+            timer: Timer::from_seconds(POWER_UP_SECONDS, TimerMode::Once),
+        });
+        commands.entity(capsule).despawn();
+    }
+}
+
+fn power_up_timer(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    mut query: Query<(Entity, &mut ActivePowerUp)>,
+) {
+    for (entity, mut power_up) in query.iter_mut() {
+        if power_up.timer.tick(clock.step()).finished() {
+            commands.entity(entity).remove::<ActivePowerUp>();
+        }
+    }
+}
+
+fn power_up_board(
+    player_query: Query<Option<&ActivePowerUp>, With<Player>>,
+    mut text_query: Query<&mut Text, With<PowerUpText>>,
+) {
+    let value = match player_query.get_single() {
+        Ok(Some(power_up)) => format!(
+            "{} {:.0}s",
+            power_up.kind.label(),
+            power_up.timer.remaining_secs().ceil()
+        ),
+        _ => String::new(),
+    };
+    for mut text in &mut text_query {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn shield_damage(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    laser_query: Query<&Laser>,
+) {
+    let mut destroyed = Vec::new();
+
+    for collision in collisions.iter() {
+        let (other, cell) = collision.entities;
+        match collision.layers {
+            // Shots are stopped by the cell they run into, piercing lasers bore through
+            (CollisionLayer::PlayerShot | CollisionLayer::EnemyShot, CollisionLayer::Shield) => {
+                if !laser_query.get(other).is_ok_and(|laser| laser.piercing) {
+                    commands.entity(other).despawn();
+                }
+            }
+            // Bugs marching through a bunker erase everything they overlap
+            (CollisionLayer::Enemy, CollisionLayer::Shield) => {}
+            _ => continue,
+        }
+
+        if !destroyed.contains(&cell) {
+            destroyed.push(cell);
+            commands.entity(cell).despawn();
+        }
+    }
+}
+
+fn mothership_spawner(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    mut rng: ResMut<GameRng>,
+    sprite_sheet: Res<SpriteSheet>,
+    mut timer: ResMut<MothershipTimer>,
+    mothership_query: Query<(), With<Mothership>>,
+) {
+    // Only one mothership at a time, the countdown resumes once it is gone
+    if !mothership_query.is_empty() || !timer.tick(clock.step()).finished() {
+        return;
+    }
+    *timer = MothershipTimer::random(&mut **rng);
+
+    let direction = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: sprite_sheet.clone(),
+            transform: Transform::from_translation(Vec3::new(
+                -direction * WINDOW_WIDTH / 2.0,
+                WINDOW_HEIGHT / 2.0 - 50.0,
+                0.0,
+            )),
+            sprite: TextureAtlasSprite {
+                index: 3,
+                color: Color::RED,
+                ..default()
+            },
+            ..default()
+        },
+        Mothership { direction },
+        Collider::new(CollisionLayer::Enemy, Vec2::new(24.0, 12.0)),
+        OnGameScreen,
+    ));
+}
+
+fn mothership_movement(
+    clock: Res<SimulationClock>,
+    mut query: Query<(Entity, &Mothership, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, mothership, mut trans) in query.iter_mut() {
+        trans.translation.x += mothership.direction * MOTHERSHIP_SPEED * clock.step_seconds();
+
+        if trans.translation.x.abs() > WINDOW_WIDTH / 2.0 + 24.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn mothership_zapper(
+    mut collisions: EventReader<Collision>,
+    laser_query: Query<&Laser>,
+    mothership_query: Query<(), With<Mothership>>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut rng: ResMut<GameRng>,
+    mut sounds: EventWriter<GameSound>,
+) {
+    let mut destroyed = Vec::new();
+
+    for collision in collisions.iter() {
+        let Some((laser, mothership)) =
+            collision.between(CollisionLayer::PlayerShot, CollisionLayer::Enemy)
+        else {
+            continue;
+        };
+        if !mothership_query.contains(mothership) {
+            continue;
+        }
+
+        if !laser_query.get(laser).is_ok_and(|laser| laser.piercing) {
+            commands.entity(laser).despawn();
+        }
+        if !destroyed.contains(&mothership) {
+            destroyed.push(mothership);
+            commands.entity(mothership).despawn();
+            **score += MOTHERSHIP_POINTS[rng.gen_range(0..MOTHERSHIP_POINTS.len())];
+            sounds.send(GameSound::Bonus);
+        }
+    }
+}
+
+fn bug_zapper(
+    mut collisions: EventReader<Collision>,
+    mut laser_query: Query<&mut Laser>,
+    mut bug_query: Query<(&mut Bug, &Transform, &mut TextureAtlasSprite)>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut rng: ResMut<GameRng>,
+    mut sounds: EventWriter<GameSound>,
+) {
+    for collision in collisions.iter() {
+        let Some((laser, bug_entity)) =
+            collision.between(CollisionLayer::PlayerShot, CollisionLayer::Enemy)
+        else {
+            continue;
+        };
+        let Ok((mut bug, bug_transform, mut sprite)) = bug_query.get_mut(bug_entity) else {
+            continue;
+        };
+
+        match laser_query.get_mut(laser) {
+            // A piercing laser overlaps the bug for a few ticks on its way through
+            Ok(mut laser) if laser.piercing => {
+                if !laser.pierced.insert(bug_entity) {
+                    continue;
+                }
+            }
+            _ => commands.entity(laser).despawn(),
+        }
+        // Already shot down by another laser during this frame
+        if bug.health == 0 {
+            continue;
+        }
+
+        bug.health -= 1;
+        if bug.health == 0 {
+            commands.entity(bug_entity).despawn();
+            **score += bug.kind.points();
+            sounds.send(GameSound::Explosion);
+
+            if rng.gen_bool(POWER_UP_DROP_CHANCE) {
+                let kind = *PowerUpKind::ALL.choose(&mut **rng).unwrap();
+                spawn_power_up(&mut commands, kind, bug_transform.translation);
+            }
+        } else {
+            // Fade wounded bugs so the player knows they are about to go down
+            sprite.color.set_a(0.5);
+        }
+    }
+}
+
+fn spawn_power_up(commands: &mut Commands, kind: PowerUpKind, position: Vec3) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(Vec2::new(10.0, 14.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        PowerUpCapsule(kind),
+        Collider::new(CollisionLayer::PowerUp, Vec2::new(10.0, 14.0)),
+        OnGameScreen,
+    ));
+}
+
+fn bug_landing(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    sprite_sheet: Res<SpriteSheet>,
+    wave: Res<Wave>,
+    player_query: Query<&Transform, With<Player>>,
+    bug_query: Query<(Entity, &Bug, &Transform)>,
+    mut life_lost_events: EventWriter<LifeLost>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    // Bugs shot down during this tick are already on their way out
+    let live_bugs = || bug_query.iter().filter(|(_, bug, _)| bug.health > 0);
+
+    let touched = collisions.iter().any(|collision| {
+        collision
+            .between(CollisionLayer::Enemy, CollisionLayer::Player)
+            .is_some()
+    });
+    let landed = touched
+        || live_bugs()
+            .any(|(_, _, trans)| trans.translation.y - player_transform.translation.y < 24.0);
+
+    if landed {
+        // Send the invaders back to the top so the next life starts from scratch
+        for (entity, ..) in live_bugs() {
+            commands.entity(entity).despawn();
+        }
+        spawn_bugs(&mut commands, &sprite_sheet, &wave);
+        life_lost_events.send(LifeLost(DeathCause::Invaded));
+    }
+}
+
+fn lose_life(
+    mut commands: Commands,
+    mut life_lost_events: EventReader<LifeLost>,
+    mut lives: ResMut<Lives>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+    mut game_state: ResMut<State<GameState>>,
+    mut sounds: EventWriter<GameSound>,
+) {
+    let lives_before = **lives;
+    let mut last_cause = None;
+    for LifeLost(cause) in life_lost_events.iter() {
+        **lives = lives.saturating_sub(1);
+        last_cause = Some(*cause);
+        sounds.send(GameSound::PlayerHit);
+    }
+    let Some(cause) = last_cause else {
+        return;
+    };
+
+    // Only once, later ticks of the same frame still run before the state changes
+    if lives_before > 0 && **lives == 0 {
+        commands.insert_resource(RunResult {
+            score: **score,
+            wave: **wave,
+            cause,
+        });
+        game_state.set(GameState::GameOver).unwrap();
+    }
+}
+
+fn next_wave(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    sprite_sheet: Res<SpriteSheet>,
+    bug_query: Query<(), With<Bug>>,
+    mut wave: ResMut<Wave>,
+    interlude: Option<ResMut<WaveInterlude>>,
+) {
+    if let Some(mut timer) = interlude {
+        if timer.tick(clock.step()).finished() {
+            **wave += 1;
+            spawn_bugs(&mut commands, &sprite_sheet, &wave);
+            commands.remove_resource::<WaveInterlude>();
+        }
+    } else if bug_query.is_empty() {
+        commands.insert_resource(WaveInterlude(Timer::from_seconds(
+            WAVE_INTERLUDE_SECONDS,
+            TimerMode::Once,
+        )));
+    }
+}
+
+fn scoreboard(score: Res<Score>, mut query: Query<&mut Text, With<ScoreText>>) {
+    if !score.is_changed() {
+        return;
+    }
+    for mut text in &mut query {
+        text.sections[1].value = format!("{:05}", **score);
+    }
+}
+
+fn lives_board(lives: Res<Lives>, mut query: Query<&mut Text, With<LivesText>>) {
+    if !lives.is_changed() {
+        return;
+    }
+    for mut text in &mut query {
+        text.sections[1].value = lives.to_string();
+    }
+}
+
+fn wave_board(wave: Res<Wave>, mut query: Query<&mut Text, With<WaveText>>) {
+    if !wave.is_changed() {
+        return;
+    }
+    for mut text in &mut query {
+        text.sections[1].value = wave.to_string();
+    }
+}
+
+// The next run gets a fresh seed, still derived from the first one
+fn next_run_seed(mut seed: ResMut<RunSeed>, mut rng: ResMut<GameRng>) {
+    **seed = rng.gen();
+}
+
+fn game_setup(
+    mut commands: Commands,
+    seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    playback: Option<Res<Playback>>,
+    asset_server: Option<Res<AssetServer>>,
+    texture_atlases: Option<ResMut<Assets<TextureAtlas>>>,
+) {
+    // A replay brings back the run it was recorded from
+    let (seed, difficulty) = match playback {
+        Some(playback) => (playback.replay().seed, playback.replay().difficulty),
+        None => (**seed, *difficulty),
+    };
+
+    // Setup the sprite sheet, headless runs have no assets and keep the default handles
+    let texture_atlas_handle = match (&asset_server, texture_atlases) {
+        (Some(asset_server), Some(mut texture_atlases)) => {
+            let texture_handle = asset_server.load("spritesheet.png");
+            let texture_atlas = TextureAtlas::from_grid(
+                texture_handle,
+                Vec2::new(24.0, 24.0),
+                6,
+                1,
+                Some(Vec2::new(0.0, 0.0)),
+                Some(Vec2::new(0.0, 0.0)),
+            );
+            texture_atlases.add(texture_atlas)
+        }
+        _ => Handle::default(),
+    };
+
+    commands.insert_resource(Score::default());
+    commands.insert_resource(PlayerInput::default());
+    commands.insert_resource(Lives(STARTING_LIVES));
+    commands.insert_resource(Wave(1));
+    commands.remove_resource::<WaveInterlude>();
+    // Every run restarts the generator, so the run only depends on its seed
+    info!("Starting run with seed {}", seed);
+    let mut rng = GameRng::from_seed(seed);
+    commands.insert_resource(MothershipTimer::random(&mut *rng));
+    commands.insert_resource(rng);
+
+    // Spawn the HUD
+    let font = asset_server
+        .map(|asset_server| asset_server.load("fonts/Monocraft.otf"))
+        .unwrap_or_default();
+    let hud_text_style = TextStyle {
+        font,
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new("SCORE ", hud_text_style.clone()),
+            TextSection::new(format!("{:05}", 0), hud_text_style.clone()),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(10.0),
+                top: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        }),
+        ScoreText,
+        OnGameScreen,
+    ));
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new("LIVES ", hud_text_style.clone()),
+            TextSection::new(STARTING_LIVES.to_string(), hud_text_style.clone()),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Px(10.0),
+                top: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        }),
+        LivesText,
+        OnGameScreen,
+    ));
+    commands.spawn((
+        TextBundle::from_section("", hud_text_style.clone()).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(10.0),
+                bottom: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        }),
+        PowerUpText,
+        OnGameScreen,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Auto),
+                    justify_content: JustifyContent::Center,
+                    position: UiRect {
+                        top: Val::Px(5.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_sections([
+                    TextSection::new("WAVE ", hud_text_style.clone()),
+                    TextSection::new("1", hud_text_style),
+                ]),
+                WaveText,
+            ));
+        });
+
+    // Spawn the player
+    commands.spawn((
+        SpriteSheetBundle {
+            texture_atlas: texture_atlas_handle.clone(),
+            transform: Transform::from_translation(Vec3::new(0.0, -(WINDOW_HEIGHT / 2.5), 0.0)),
+            sprite: TextureAtlasSprite {
+                index: 0,
+                color: Color::LIME_GREEN,
+                ..default()
+            },
+            ..default()
+        },
+        Player { delta_x: 0.0 },
+        difficulty.weapon(),
+        Collider::new(CollisionLayer::Player, Vec2::splat(24.0)),
+        OnGameScreen,
+    ));
+
+    spawn_bugs(&mut commands, &texture_atlas_handle, &Wave(1));
+    spawn_shields(&mut commands);
+    commands.insert_resource(SpriteSheet(texture_atlas_handle));
+}
+
+fn spawn_shields(commands: &mut Commands) {
+    let spacing = WINDOW_WIDTH / SHIELD_COUNT as f32;
+    let width = SHIELD_SHAPE[0].len() as f32 * SHIELD_CELL_SIZE;
+    let top = -(WINDOW_HEIGHT / 2.5) + 100.0;
+
+    for shield in 0..SHIELD_COUNT {
+        let left = -(WINDOW_WIDTH / 2.0) + spacing * (shield as f32 + 0.5) - width / 2.0;
+        for (row, line) in SHIELD_SHAPE.iter().enumerate() {
+            for (col, _) in line.chars().enumerate().filter(|(_, c)| *c == '#') {
+                let x = left + (col as f32 + 0.5) * SHIELD_CELL_SIZE;
+                let y = top - (row as f32 + 0.5) * SHIELD_CELL_SIZE;
+
+                commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::LIME_GREEN,
+                            custom_size: Some(Vec2::splat(SHIELD_CELL_SIZE)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(Vec3::new(x, y, 0.0)),
+                        ..default()
+                    },
+                    ShieldCell,
+                    Collider::new(CollisionLayer::Shield, Vec2::splat(SHIELD_CELL_SIZE)),
+                    OnGameScreen,
+                ));
+            }
+        }
+    }
+}
+
+fn spawn_bugs(commands: &mut Commands, texture_atlas_handle: &Handle<TextureAtlas>, wave: &Wave) {
+    const ROWS: u32 = 4;
+    const COLUMNS: u32 = 20;
+
+    // Every new formation starts marching from scratch
+    commands.insert_resource(Formation {
+        movement: BugMovement::Left,
+        size: (ROWS * COLUMNS) as usize,
+    });
+
+    // Spawn rows of enemies, the ones further away are tougher and worth more points
+    for bug_row in 0..ROWS {
+        let y = 200.0 - wave.start_drop() - (bug_row as f32 * 30.0);
+        let kind = BugKind::for_row(bug_row);
+        for bug_col in 0..COLUMNS {
+            let x = -300.0 + (bug_col as f32 * 30.0);
+
+            commands.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: texture_atlas_handle.clone(),
+                    transform: Transform::from_translation(Vec3::new(x, y, 0.0)),
+                    sprite: TextureAtlasSprite {
+                        index: kind.sprite_index(),
+                        color: Color::LIME_GREEN,
+                        ..default()
+                    },
+                    ..default()
+                },
+                Bug {
+                    kind,
+                    health: kind.hit_points(),
+                    column: bug_col,
+                },
+                Collider::new(CollisionLayer::Enemy, Vec2::splat(24.0)),
+                OnGameScreen,
+            ));
+        }
+    }
+}
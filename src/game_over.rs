@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+
+use crate::{
+    despawn_screen,
+    game::{DeathCause, RunResult},
+    menu::{
+        button_system, first_clicked, menu_navigation, spawn_menu_button, InteractionQueryChanged,
+        MenuNavigation,
+    },
+    GameState, TEXT_COLOR,
+};
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over_setup))
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(game_over_action.after(MenuNavigation))
+                    .with_system(button_system.after(MenuNavigation))
+                    .with_system(menu_navigation.label(MenuNavigation)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(despawn_screen::<OnGameOverScreen>),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OnGameOverScreen;
+
+// All actions that can be triggered from a button click on the game over screen
+#[derive(Component)]
+enum GameOverButtonAction {
+    Retry,
+    BackToMainMenu,
+}
+
+fn game_over_setup(mut commands: Commands, asset_server: Res<AssetServer>, result: Res<RunResult>) {
+    let font = asset_server.load("fonts/Monocraft.otf");
+    let button_style = Style {
+        size: Size::new(Val::Px(250.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font: font.clone(),
+        font_size: 30.0,
+        color: TEXT_COLOR,
+    };
+
+    let cause = match result.cause {
+        DeathCause::Invaded => "The invaders landed",
+        DeathCause::ShotDown => "Shot down by the invaders",
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::LIME_GREEN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Game Over",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 80.0,
+                                color: TEXT_COLOR,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+
+                    // Display how far the run went and how it ended
+                    for line in [
+                        format!("Score {:05}", result.score),
+                        format!("Wave {}", result.wave),
+                        cause.to_string(),
+                    ] {
+                        parent.spawn(
+                            TextBundle::from_section(line, button_text_style.clone()).with_style(
+                                Style {
+                                    margin: UiRect::all(Val::Px(5.0)),
+                                    ..default()
+                                },
+                            ),
+                        );
+                    }
+
+                    for (action, text) in [
+                        (GameOverButtonAction::Retry, "Retry"),
+                        (GameOverButtonAction::BackToMainMenu, "Main Menu"),
+                    ] {
+                        let is_back = matches!(action, GameOverButtonAction::BackToMainMenu);
+                        spawn_menu_button(
+                            parent,
+                            action,
+                            text,
+                            &button_style,
+                            &button_text_style,
+                            is_back,
+                        );
+                    }
+                });
+        });
+}
+
+fn game_over_action(
+    interaction_query: Query<(&Interaction, &GameOverButtonAction), InteractionQueryChanged>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    let Some(game_over_button_action) = first_clicked(&interaction_query) else {
+        return;
+    };
+    let _ = match game_over_button_action {
+        GameOverButtonAction::Retry => game_state.set(GameState::Game),
+        GameOverButtonAction::BackToMainMenu => game_state.set(GameState::Menu),
+    };
+}
@@ -0,0 +1,92 @@
+//! Runs the game logic without a window, graphics or assets, for machines without a display
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{GamePlugin, Lives, Score, SimulationClock, Wave},
+    replay::{Playback, Replay, ReplayPlugin},
+    GameState,
+};
+
+// Where a headless run stands after the simulated ticks
+#[derive(Debug)]
+pub struct HeadlessReport {
+    pub ticks: u64,
+    pub state: GameState,
+    pub score: u32,
+    pub lives: u32,
+    pub wave: u32,
+}
+
+impl std::fmt::Display for HeadlessReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "tick {}: {:?}, score {}, lives {}, wave {}",
+            self.ticks, self.state, self.score, self.lives, self.wave
+        )
+    }
+}
+
+// App running a single game, advanced only by `advance`
+pub fn app(seed: Option<u64>) -> App {
+    let mut app = new_app(seed);
+    // Enter the game before any tick is simulated
+    app.update();
+    app
+}
+
+// App playing `replay` back, advanced only by `advance`
+pub fn replay_app(replay: Replay) -> App {
+    let mut app = new_app(Some(replay.seed));
+    app.insert_resource(Playback::new(replay));
+    app.update();
+    app
+}
+
+fn new_app(seed: Option<u64>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(SimulationClock::manual(60))
+        .add_state(GameState::Game)
+        .add_plugin(GamePlugin { seed })
+        .add_plugin(ReplayPlugin::default());
+    app
+}
+
+// Simulates `ticks` ticks, one per update, or fewer if the game ends first
+pub fn advance(app: &mut App, ticks: u32) {
+    for _ in 0..ticks {
+        if *app.world.resource::<State<GameState>>().current() != GameState::Game {
+            break;
+        }
+        app.world.resource_mut::<SimulationClock>().queue_ticks(1);
+        app.update();
+    }
+}
+
+pub fn report(app: &App) -> HeadlessReport {
+    let world = &app.world;
+    HeadlessReport {
+        ticks: world.resource::<SimulationClock>().ticks(),
+        state: world.resource::<State<GameState>>().current().clone(),
+        score: **world.resource::<Score>(),
+        lives: **world.resource::<Lives>(),
+        wave: **world.resource::<Wave>(),
+    }
+}
+
+pub fn run(ticks: u32, seed: Option<u64>) -> HeadlessReport {
+    let mut app = app(seed);
+    advance(&mut app, ticks);
+    report(&app)
+}
+
+// Plays `replay` back until its run is over
+pub fn play_replay(replay: Replay) -> HeadlessReport {
+    // One more tick than recorded, for the end of the run to go through
+    let ticks = replay.ticks() + 1;
+    let mut app = replay_app(replay);
+    advance(&mut app, ticks);
+    report(&app)
+}
@@ -0,0 +1,65 @@
+use std::{fmt, io};
+
+use bevy::prelude::*;
+
+pub mod audio;
+pub mod game;
+pub mod game_over;
+pub mod headless;
+pub mod menu;
+pub mod replay;
+pub mod settings;
+pub mod splash;
+
+pub const WINDOW_WIDTH: f32 = 800.0;
+pub const WINDOW_HEIGHT: f32 = 600.0;
+
+pub const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum GameState {
+    Splash,
+    Menu,
+    Game,
+    Paused,
+    GameOver,
+}
+
+// Why a settings or replay file couldn't be read or written
+#[derive(Debug)]
+pub enum FileError {
+    Io(io::Error),
+    Format(ron::error::SpannedError),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileError::Io(error) => write!(f, "{error}"),
+            FileError::Format(error) => write!(
+                f,
+                "invalid contents at line {}, column {}: {}",
+                error.position.line, error.position.col, error.code
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for FileError {
+    fn from(error: io::Error) -> Self {
+        FileError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for FileError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        FileError::Format(error)
+    }
+}
+
+// Generic system that takes a component as a parameter, and will despawn all entities with that component
+pub fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
+    for entity in &to_despawn {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,1164 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    despawn_screen,
+    game::{ControlAction, Controls, Difficulty},
+    replay::{Playback, Replay, ReplayFile},
+    settings::{
+        DisplayMode, EffectsVolume, MasterVolume, MusicVolume, Resolution, Vsync, MAX_VOLUME,
+    },
+    GameState, TEXT_COLOR,
+};
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state(MenuState::Disabled)
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(menu_setup))
+            .add_system_set(SystemSet::on_enter(MenuState::Main).with_system(main_menu_setup))
+            .add_system_set(
+                SystemSet::on_exit(MenuState::Main).with_system(despawn_screen::<OnMainMenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(MenuState::Settings).with_system(settings_menu_setup),
+            )
+            .add_system_set(
+                SystemSet::on_update(MenuState::Settings)
+                    .with_system(setting_button::<Difficulty>.after(MenuNavigation)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(MenuState::Settings)
+                    .with_system(despawn_screen::<OnSettingsMenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(MenuState::DisplaySettings)
+                    .with_system(display_settings_menu_setup),
+            )
+            .add_system_set(
+                SystemSet::on_update(MenuState::DisplaySettings)
+                    .with_system(setting_button::<DisplayMode>.after(MenuNavigation))
+                    .with_system(setting_button::<Resolution>.after(MenuNavigation))
+                    .with_system(setting_button::<Vsync>.after(MenuNavigation)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(MenuState::DisplaySettings)
+                    .with_system(despawn_screen::<OnDisplaySettingsMenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(MenuState::SoundSettings)
+                    .with_system(sound_settings_menu_setup),
+            )
+            .add_system_set(
+                SystemSet::on_update(MenuState::SoundSettings)
+                    .with_system(setting_button::<MasterVolume>.after(MenuNavigation))
+                    .with_system(setting_button::<MusicVolume>.after(MenuNavigation))
+                    .with_system(setting_button::<EffectsVolume>.after(MenuNavigation)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(MenuState::SoundSettings)
+                    .with_system(despawn_screen::<OnSoundSettingsMenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(MenuState::ControlsSettings)
+                    .with_system(controls_settings_menu_setup),
+            )
+            .add_system_set(
+                SystemSet::on_update(MenuState::ControlsSettings)
+                    .with_system(rebind_prompt.after(MenuNavigation))
+                    // Gets the pressed key before the menu navigation uses it
+                    .with_system(rebind_controls.before(MenuNavigation)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(MenuState::ControlsSettings)
+                    .with_system(despawn_screen::<OnControlsSettingsMenuScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(pause_menu_open))
+            .add_system_set(SystemSet::on_enter(MenuState::Pause).with_system(pause_menu_setup))
+            .add_system_set(
+                SystemSet::on_exit(MenuState::Pause)
+                    .with_system(despawn_screen::<OnPauseMenuScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(menu_action.after(MenuNavigation))
+                    .with_system(button_system.after(MenuNavigation))
+                    .with_system(menu_navigation.label(MenuNavigation)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(menu_action.after(MenuNavigation))
+                    .with_system(button_system.after(MenuNavigation))
+                    .with_system(menu_navigation.label(MenuNavigation)),
+            );
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+enum MenuState {
+    Main,
+    Settings,
+    DisplaySettings,
+    SoundSettings,
+    ControlsSettings,
+    Pause,
+    Disabled,
+}
+
+#[derive(Component)]
+struct OnMainMenuScreen;
+
+#[derive(Component)]
+struct OnSettingsMenuScreen;
+
+#[derive(Component)]
+struct OnPauseMenuScreen;
+
+#[derive(Component)]
+struct OnDisplaySettingsMenuScreen;
+
+#[derive(Component)]
+struct OnSoundSettingsMenuScreen;
+
+#[derive(Component)]
+struct OnControlsSettingsMenuScreen;
+
+// Text telling what the controls screen waits for, along with the action being rebound
+#[derive(Component)]
+struct RebindPrompt(Option<ControlAction>);
+
+pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const HOVERED_PRESSED_BUTTON: Color = Color::rgb(0.25, 0.65, 0.25);
+const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+
+// Tag component used to mark wich setting is currently selected
+#[derive(Component)]
+pub struct SelectedOption;
+
+// Tag component used to mark the button driven by the keyboard or a gamepad
+#[derive(Component)]
+pub struct Focused;
+
+// Tag component used to mark the button that Escape or the B button activates
+#[derive(Component)]
+pub struct BackButton;
+
+// All actions that can be triggered from a button click
+#[derive(Component)]
+enum MenuButtonAction {
+    Play,
+    WatchReplay,
+    Settings,
+    DisplaySettings,
+    SoundSettings,
+    ControlsSettings,
+    BackToSettings,
+    BackToMainMenu,
+    BackToPauseMenu,
+    Resume,
+    QuitToMenu,
+    Quit,
+}
+
+pub type InteractionQueryChanged = (Changed<Interaction>, With<Button>);
+
+// What the button clicked this frame does. The mouse and the keyboard can click two buttons in
+// the same frame, only the first counts
+pub fn first_clicked<'a, T: Component>(
+    interaction_query: &'a Query<(&Interaction, &T), InteractionQueryChanged>,
+) -> Option<&'a T> {
+    interaction_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, action)| action)
+}
+
+#[allow(clippy::type_complexity)]
+pub fn button_system(
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            Option<&SelectedOption>,
+            Option<&Focused>,
+        ),
+        InteractionQueryChanged,
+    >,
+) {
+    for (interaction, mut color, selected, focused) in &mut interaction_query {
+        *color = button_color(*interaction, selected.is_some(), focused.is_some());
+    }
+}
+
+// A focused button looks the same as a hovered one
+fn button_color(interaction: Interaction, selected: bool, focused: bool) -> BackgroundColor {
+    let hovered = interaction == Interaction::Hovered || focused;
+    match (interaction, selected, hovered) {
+        (Interaction::Clicked, _, _) | (_, true, false) => PRESSED_BUTTON.into(),
+        (_, true, true) => HOVERED_PRESSED_BUTTON.into(),
+        (_, false, true) => HOVERED_BUTTON.into(),
+        (_, false, false) => NORMAL_BUTTON.into(),
+    }
+}
+
+type NavigationQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static GlobalTransform,
+        &'static mut Interaction,
+        &'static mut BackgroundColor,
+        Option<&'static SelectedOption>,
+        Option<&'static Focused>,
+        Option<&'static BackButton>,
+    ),
+    With<Button>,
+>;
+
+// The system clicking buttons from the keyboard or a gamepad, anything reacting to a click runs
+// after it
+#[derive(SystemLabel)]
+pub struct MenuNavigation;
+
+// Moves the focus between the buttons on screen with the arrow keys or the D-pad, and clicks
+// the focused button with Enter or A, or the back button with Escape or B
+pub fn menu_navigation(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut gamepad_input: ResMut<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    mut buttons: NavigationQuery,
+    mut clicked: Local<Option<Entity>>,
+) {
+    // Releases the button clicked last frame, as the mouse would
+    if let Some(entity) = clicked.take() {
+        if let Ok((_, _, mut interaction, ..)) = buttons.get_mut(entity) {
+            if *interaction == Interaction::Clicked {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+
+    let mut just_pressed = |key: KeyCode, button: GamepadButtonType| {
+        let pad_button = gamepads
+            .iter()
+            .map(|gamepad| GamepadButton::new(gamepad, button))
+            .find(|pad_button| gamepad_input.just_pressed(*pad_button));
+        // The press is used up here, so the screen shown next doesn't see it as well
+        if let Some(pad_button) = pad_button {
+            gamepad_input.clear_just_pressed(pad_button);
+            true
+        } else if keyboard_input.just_pressed(key) {
+            keyboard_input.clear_just_pressed(key);
+            true
+        } else {
+            false
+        }
+    };
+    let direction = [
+        (KeyCode::Up, GamepadButtonType::DPadUp, Vec2::NEG_Y),
+        (KeyCode::Down, GamepadButtonType::DPadDown, Vec2::Y),
+        (KeyCode::Left, GamepadButtonType::DPadLeft, Vec2::NEG_X),
+        (KeyCode::Right, GamepadButtonType::DPadRight, Vec2::X),
+    ]
+    .into_iter()
+    .find_map(|(key, button, direction)| just_pressed(key, button).then_some(direction));
+    let activate = just_pressed(KeyCode::Return, GamepadButtonType::South);
+    let back = just_pressed(KeyCode::Escape, GamepadButtonType::East);
+
+    let focused = buttons
+        .iter()
+        .find(|(.., focused, _)| focused.is_some())
+        .map(|(entity, transform, ..)| (entity, transform.translation().truncate()));
+    // Hovering a button with the mouse moves the focus there too
+    let hovered = buttons
+        .iter_mut()
+        .find(|(entity, _, interaction, ..)| {
+            interaction.is_changed()
+                && **interaction == Interaction::Hovered
+                && Some(*entity) != focused.map(|(entity, _)| entity)
+        })
+        .map(|(entity, ..)| entity);
+
+    let target = if back {
+        let back_button = buttons
+            .iter()
+            .find(|(.., back_button)| back_button.is_some());
+        back_button.map(|(entity, ..)| (entity, true))
+    } else if let Some((entity, position)) = focused {
+        if activate {
+            Some((entity, true))
+        } else if let Some(direction) = direction {
+            // The closest button that way, straying off the axis counting double
+            buttons
+                .iter()
+                .filter_map(|(other, transform, ..)| {
+                    let offset = transform.translation().truncate() - position;
+                    let along = offset.dot(direction);
+                    let across = offset.perp_dot(direction).abs();
+                    (along > 1.0).then_some((other, along + 2.0 * across))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(other, _)| (other, false))
+        } else {
+            hovered.map(|entity| (entity, false))
+        }
+    } else if direction.is_some() || activate {
+        // The first press only shows where the focus starts, at the top left button
+        buttons
+            .iter()
+            .map(|(entity, transform, ..)| (entity, transform.translation()))
+            .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+            .map(|(entity, _)| (entity, false))
+    } else {
+        hovered.map(|entity| (entity, false))
+    };
+
+    let Some((target, click)) = target else {
+        return;
+    };
+    if let Some((previous, _)) = focused.filter(|(previous, _)| *previous != target) {
+        if let Ok((_, _, interaction, mut color, selected, ..)) = buttons.get_mut(previous) {
+            *color = button_color(*interaction, selected.is_some(), false);
+        }
+        commands.entity(previous).remove::<Focused>();
+    }
+    if let Ok((_, _, mut interaction, mut color, selected, ..)) = buttons.get_mut(target) {
+        if click {
+            *interaction = Interaction::Clicked;
+            *clicked = Some(target);
+        }
+        *color = button_color(*interaction, selected.is_some(), true);
+    }
+    commands.entity(target).insert(Focused);
+}
+
+// Generic system that keeps a setting resource in sync with the buttons holding its values
+#[allow(clippy::type_complexity)]
+fn setting_button<T: Resource + Component + PartialEq + Copy>(
+    interaction_query: Query<(&Interaction, &T, Entity), InteractionQueryChanged>,
+    mut selected_query: Query<(Entity, &mut BackgroundColor), (With<SelectedOption>, With<T>)>,
+    mut commands: Commands,
+    mut setting: ResMut<T>,
+) {
+    for (interaction, button_setting, entity) in &interaction_query {
+        if *interaction == Interaction::Clicked && *setting != *button_setting {
+            // A value that isn't offered on screen has no button to unselect
+            if let Ok((previous_button, mut previous_color)) = selected_query.get_single_mut() {
+                *previous_color = NORMAL_BUTTON.into();
+                commands.entity(previous_button).remove::<SelectedOption>();
+            }
+            commands.entity(entity).insert(SelectedOption);
+            *setting = *button_setting;
+        }
+    }
+}
+
+fn menu_setup(mut menu_state: ResMut<State<MenuState>>) {
+    let _ = menu_state.set(MenuState::Main);
+}
+
+fn pause_menu_open(mut menu_state: ResMut<State<MenuState>>) {
+    let _ = menu_state.set(MenuState::Pause);
+}
+
+fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Monocraft.otf");
+    // Common style for all buttons on the screen
+    let button_style = Style {
+        size: Size::new(Val::Px(250.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(12.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_icon_style = Style {
+        size: Size::new(Val::Px(30.0), Val::Auto),
+        // This takes the icons out of the flexbox flow, to be positioned exactly
+        position_type: PositionType::Absolute,
+        // The icon will be close to the left border of the button
+        position: UiRect {
+            left: Val::Px(10.0),
+            right: Val::Auto,
+            top: Val::Auto,
+            bottom: Val::Auto,
+        },
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font: font.clone(),
+        font_size: 30.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnMainMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::LIME_GREEN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Display the game name
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Cosmo Invaders",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 80.0,
+                                color: TEXT_COLOR,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+
+                    // Display four buttons for each action available from the main menu:
+                    // - new game
+                    // - replay of the last run
+                    // - settings
+                    // - quit
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::Play,
+                        ))
+                        .with_children(|parent| {
+                            let icon = asset_server.load("textures/Game Icons/right.png");
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                image: icon.into(),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "New Game",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::WatchReplay,
+                        ))
+                        .with_children(|parent| {
+                            let icon = asset_server.load("textures/Game Icons/replay.png");
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                image: icon.into(),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "Replay",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::Settings,
+                        ))
+                        .with_children(|parent| {
+                            let icon = asset_server.load("textures/Game Icons/wrench.png");
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                image: icon.into(),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "Settings",
+                                button_text_style.clone(),
+                            ));
+                        });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::Quit,
+                        ))
+                        .with_children(|parent| {
+                            let icon = asset_server.load("textures/Game Icons/exitRight.png");
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style,
+                                image: icon.into(),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section("Quit", button_text_style));
+                        });
+                });
+        });
+}
+
+fn pause_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let button_style = Style {
+        size: Size::new(Val::Px(300.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let font = asset_server.load("fonts/Monocraft.otf");
+    let button_text_style = TextStyle {
+        font: font.clone(),
+        font_size: 30.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                // Dim the frozen game behind the menu
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            OnPauseMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::LIME_GREEN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Paused",
+                            TextStyle {
+                                font,
+                                font_size: 60.0,
+                                color: TEXT_COLOR,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+
+                    for (action, text) in [
+                        (MenuButtonAction::Resume, "Resume"),
+                        (MenuButtonAction::Settings, "Settings"),
+                        (MenuButtonAction::QuitToMenu, "Quit to Menu"),
+                    ] {
+                        // Going back from the pause menu resumes the game
+                        let is_back = matches!(action, MenuButtonAction::Resume);
+                        spawn_menu_button(
+                            parent,
+                            action,
+                            text,
+                            &button_style,
+                            &button_text_style,
+                            is_back,
+                        );
+                    }
+                });
+        });
+}
+
+fn settings_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_state: Res<State<GameState>>,
+    difficulty: Res<Difficulty>,
+) {
+    let button_style = Style {
+        size: Size::new(Val::Px(300.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(12.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let option_button_style = Style {
+        size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+        margin: UiRect::all(Val::Px(10.0)),
+        ..button_style.clone()
+    };
+
+    let button_text_style = TextStyle {
+        font: asset_server.load("fonts/Monocraft.otf"),
+        font_size: 30.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnSettingsMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::LIME_GREEN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Display the difficulty options, highlighting the current one
+                    spawn_setting_row(
+                        parent,
+                        "Difficulty",
+                        [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard]
+                            .map(|setting| (setting, format!("{setting:?}"))),
+                        *difficulty,
+                        &option_button_style,
+                        &button_text_style,
+                    );
+
+                    // The settings can also be opened from the pause menu, go back there
+                    let back_action = if *game_state.current() == GameState::Paused {
+                        MenuButtonAction::BackToPauseMenu
+                    } else {
+                        MenuButtonAction::BackToMainMenu
+                    };
+                    for (action, text) in [
+                        (MenuButtonAction::DisplaySettings, "Display"),
+                        (MenuButtonAction::SoundSettings, "Sound"),
+                        (MenuButtonAction::ControlsSettings, "Controls"),
+                        (back_action, "Back"),
+                    ] {
+                        let is_back = matches!(
+                            action,
+                            MenuButtonAction::BackToMainMenu | MenuButtonAction::BackToPauseMenu
+                        );
+                        spawn_menu_button(
+                            parent,
+                            action,
+                            text,
+                            &button_style,
+                            &button_text_style,
+                            is_back,
+                        );
+                    }
+                });
+        });
+}
+
+fn display_settings_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    display_mode: Res<DisplayMode>,
+    resolution: Res<Resolution>,
+    vsync: Res<Vsync>,
+) {
+    let button_style = Style {
+        size: Size::new(Val::Px(300.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let option_button_style = Style {
+        size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+        margin: UiRect::all(Val::Px(10.0)),
+        ..button_style.clone()
+    };
+
+    let button_text_style = TextStyle {
+        font: asset_server.load("fonts/Monocraft.otf"),
+        font_size: 30.0,
+        color: TEXT_COLOR,
+    };
+    let option_text_style = TextStyle {
+        font_size: 20.0,
+        ..button_text_style.clone()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnDisplaySettingsMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::LIME_GREEN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_setting_row(
+                        parent,
+                        "Mode",
+                        DisplayMode::ALL.map(|setting| (setting, format!("{setting:?}"))),
+                        *display_mode,
+                        &option_button_style,
+                        &option_text_style,
+                    );
+                    spawn_setting_row(
+                        parent,
+                        "Size",
+                        Resolution::ALL.map(|setting| (setting, setting.to_string())),
+                        *resolution,
+                        &option_button_style,
+                        &option_text_style,
+                    );
+                    spawn_setting_row(
+                        parent,
+                        "VSync",
+                        [Vsync::On, Vsync::Off].map(|setting| (setting, format!("{setting:?}"))),
+                        *vsync,
+                        &option_button_style,
+                        &option_text_style,
+                    );
+
+                    spawn_menu_button(
+                        parent,
+                        MenuButtonAction::BackToSettings,
+                        "Back",
+                        &button_style,
+                        &button_text_style,
+                        true,
+                    );
+                });
+        });
+}
+
+fn sound_settings_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    master_volume: Res<MasterVolume>,
+    music_volume: Res<MusicVolume>,
+    effects_volume: Res<EffectsVolume>,
+) {
+    let button_style = Style {
+        size: Size::new(Val::Px(300.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let level_button_style = Style {
+        size: Size::new(Val::Px(30.0), Val::Px(50.0)),
+        margin: UiRect::all(Val::Px(3.0)),
+        ..button_style.clone()
+    };
+
+    let button_text_style = TextStyle {
+        font: asset_server.load("fonts/Monocraft.otf"),
+        font_size: 30.0,
+        color: TEXT_COLOR,
+    };
+    let level_text_style = TextStyle {
+        font_size: 15.0,
+        ..button_text_style.clone()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnSoundSettingsMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::LIME_GREEN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let levels = || (0..=MAX_VOLUME).map(|level| (level, level.to_string()));
+                    spawn_setting_row(
+                        parent,
+                        "Master ",
+                        levels().map(|(level, text)| (MasterVolume(level), text)),
+                        *master_volume,
+                        &level_button_style,
+                        &level_text_style,
+                    );
+                    spawn_setting_row(
+                        parent,
+                        "Music  ",
+                        levels().map(|(level, text)| (MusicVolume(level), text)),
+                        *music_volume,
+                        &level_button_style,
+                        &level_text_style,
+                    );
+                    spawn_setting_row(
+                        parent,
+                        "Effects",
+                        levels().map(|(level, text)| (EffectsVolume(level), text)),
+                        *effects_volume,
+                        &level_button_style,
+                        &level_text_style,
+                    );
+
+                    spawn_menu_button(
+                        parent,
+                        MenuButtonAction::BackToSettings,
+                        "Back",
+                        &button_style,
+                        &button_text_style,
+                        true,
+                    );
+                });
+        });
+}
+
+fn controls_settings_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    controls: Res<Controls>,
+) {
+    let button_style = Style {
+        size: Size::new(Val::Px(300.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let key_button_style = Style {
+        size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+        margin: UiRect::all(Val::Px(10.0)),
+        ..button_style.clone()
+    };
+
+    let button_text_style = TextStyle {
+        font: asset_server.load("fonts/Monocraft.otf"),
+        font_size: 30.0,
+        color: TEXT_COLOR,
+    };
+    let key_text_style = TextStyle {
+        font_size: 20.0,
+        ..button_text_style.clone()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnControlsSettingsMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::LIME_GREEN.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Display the key bound to each action, clicking it waits for a new one
+                    for action in ControlAction::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: Color::LIME_GREEN.into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    format!("{action:?}"),
+                                    button_text_style.clone(),
+                                ));
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: key_button_style.clone(),
+                                            background_color: NORMAL_BUTTON.into(),
+                                            ..default()
+                                        },
+                                        action,
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            format!("{:?}", controls.key(action)),
+                                            key_text_style.clone(),
+                                        ));
+                                    });
+                            });
+                    }
+
+                    parent.spawn((
+                        TextBundle::from_section("", key_text_style).with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                        RebindPrompt(None),
+                    ));
+
+                    spawn_menu_button(
+                        parent,
+                        MenuButtonAction::BackToSettings,
+                        "Back",
+                        &button_style,
+                        &button_text_style,
+                        true,
+                    );
+                });
+        });
+}
+
+// Clicking an action's button asks for its new key
+fn rebind_prompt(
+    interaction_query: Query<(&Interaction, &ControlAction), InteractionQueryChanged>,
+    mut prompt_query: Query<(&mut RebindPrompt, &mut Text)>,
+) {
+    let Ok((mut prompt, mut prompt_text)) = prompt_query.get_single_mut() else {
+        return;
+    };
+
+    if let Some(action) = first_clicked(&interaction_query) {
+        prompt.0 = Some(*action);
+        prompt_text.sections[0].value = format!("Press a key for {action:?}");
+    }
+}
+
+// Binds the next key pressed once an action is picked, unless another action already uses it.
+// Escape gives up on the change
+fn rebind_controls(
+    key_buttons: Query<(&ControlAction, &Children)>,
+    mut key_texts: Query<&mut Text, Without<RebindPrompt>>,
+    mut prompt_query: Query<(&mut RebindPrompt, &mut Text)>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut controls: ResMut<Controls>,
+) {
+    let Ok((mut prompt, mut prompt_text)) = prompt_query.get_single_mut() else {
+        return;
+    };
+
+    let Some(action) = prompt.0 else {
+        return;
+    };
+    let Some(key) = keyboard_input.get_just_pressed().next().copied() else {
+        return;
+    };
+    // The key is only meant for the binding, it mustn't move the focus or leave the screen
+    keyboard_input.clear_just_pressed(key);
+    prompt.0 = None;
+
+    prompt_text.sections[0].value = match key {
+        KeyCode::Escape => String::new(),
+        key => match controls.bind(action, key) {
+            Ok(()) => {
+                for (_, children) in key_buttons.iter().filter(|(other, _)| **other == action) {
+                    if let Ok(mut text) = key_texts.get_mut(children[0]) {
+                        text.sections[0].value = format!("{key:?}");
+                    }
+                }
+                String::new()
+            }
+            Err(other) => format!("{key:?} is already used by {other:?}"),
+        },
+    };
+}
+
+// A label followed by a button for each value of a setting, the current one being selected
+// A button doing `action` when clicked, the back button is also clicked by Escape or B
+pub fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    action: impl Component,
+    text: &str,
+    button_style: &Style,
+    text_style: &TextStyle,
+    back: bool,
+) {
+    let mut button = parent.spawn((
+        ButtonBundle {
+            style: button_style.clone(),
+            background_color: NORMAL_BUTTON.into(),
+            ..default()
+        },
+        action,
+    ));
+    button.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(text, text_style.clone()));
+    });
+    if back {
+        button.insert(BackButton);
+    }
+}
+
+fn spawn_setting_row<T: Component + PartialEq + Copy>(
+    parent: &mut ChildBuilder,
+    label: &str,
+    options: impl IntoIterator<Item = (T, String)>,
+    current: T,
+    button_style: &Style,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: Color::LIME_GREEN.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            for (setting, text) in options {
+                let mut entity = parent.spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    setting,
+                ));
+                entity.with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(text, text_style.clone()));
+                });
+                if current == setting {
+                    entity.insert(SelectedOption);
+                }
+            }
+        });
+}
+
+fn menu_action(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &MenuButtonAction), InteractionQueryChanged>,
+    replay_file: Option<Res<ReplayFile>>,
+    mut app_exit_events: EventWriter<AppExit>,
+    mut menu_state: ResMut<State<MenuState>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    let Some(menu_button_action) = first_clicked(&interaction_query) else {
+        return;
+    };
+    // A state change can still be turned down if another one is already queued this frame
+    match menu_button_action {
+        MenuButtonAction::Quit => app_exit_events.send(AppExit),
+        MenuButtonAction::Play => {
+            let _ = game_state.set(GameState::Game);
+            let _ = menu_state.set(MenuState::Disabled);
+        }
+        // Plays the last finished run again, through the game itself
+        MenuButtonAction::WatchReplay => {
+            let Some(ReplayFile(path)) = replay_file.as_deref() else {
+                return;
+            };
+            match Replay::load(path) {
+                Ok(replay) => {
+                    commands.insert_resource(Playback::new(replay));
+                    let _ = game_state.set(GameState::Game);
+                    let _ = menu_state.set(MenuState::Disabled);
+                }
+                Err(error) => warn!("Couldn't load {}: {error}", path.display()),
+            }
+        }
+        MenuButtonAction::Settings | MenuButtonAction::BackToSettings => {
+            let _ = menu_state.set(MenuState::Settings);
+        }
+        MenuButtonAction::DisplaySettings => {
+            let _ = menu_state.set(MenuState::DisplaySettings);
+        }
+        MenuButtonAction::SoundSettings => {
+            let _ = menu_state.set(MenuState::SoundSettings);
+        }
+        MenuButtonAction::ControlsSettings => {
+            let _ = menu_state.set(MenuState::ControlsSettings);
+        }
+        MenuButtonAction::BackToMainMenu => {
+            let _ = menu_state.set(MenuState::Main);
+        }
+        MenuButtonAction::BackToPauseMenu => {
+            let _ = menu_state.set(MenuState::Pause);
+        }
+        MenuButtonAction::Resume => {
+            let _ = game_state.pop();
+            let _ = menu_state.set(MenuState::Disabled);
+        }
+        // Unwinds the paused game so it gets cleaned up on its way out
+        MenuButtonAction::QuitToMenu => {
+            let _ = game_state.replace(GameState::Menu);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Records the player's input on every tick, so a run can be played back exactly as it happened
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{Difficulty, FixedUpdateStage, PlayerInput, PlayerSystem, RunResult, RunSeed},
+    FileError, GameState,
+};
+
+#[derive(Default)]
+pub struct ReplayPlugin {
+    // Where finished runs are saved, and watched again from the main menu
+    pub file: Option<PathBuf>,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(file) = &self.file {
+            app.insert_resource(ReplayFile(file.clone()));
+        }
+
+        app.add_system_set(SystemSet::on_enter(GameState::Game).with_system(start_recording))
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::new()
+                    .with_system(play_input.before(PlayerSystem))
+                    .with_system(record_input.after(play_input).before(PlayerSystem)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver)
+                    .with_system(finish_recording)
+                    .with_system(stop_playback.after(finish_recording)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(stop_playback));
+    }
+}
+
+// Everything needed to play a run again: its seed, its settings and the input of every tick
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    // Runs of ticks sharing the same input
+    pub inputs: Vec<(u32, PlayerInput)>,
+    // How the run ended, to check that playing it back still gives the same result
+    pub score: u32,
+    pub wave: u32,
+}
+
+impl Replay {
+    pub fn new(seed: u64, difficulty: Difficulty) -> Self {
+        Self {
+            seed,
+            difficulty,
+            inputs: Vec::new(),
+            score: 0,
+            wave: 1,
+        }
+    }
+
+    pub fn push(&mut self, input: PlayerInput) {
+        match self.inputs.last_mut() {
+            Some((ticks, last)) if *last == input => *ticks += 1,
+            _ => self.inputs.push((1, input)),
+        }
+    }
+
+    // Number of ticks recorded
+    pub fn ticks(&self) -> u32 {
+        self.inputs.iter().map(|(ticks, _)| ticks).sum()
+    }
+
+    // The last finished run in the platform's data directory, if it has one
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("cosmo_invaders").join("last_run.ron"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, FileError> {
+        let text = fs::read_to_string(path)?;
+        Ok(ron::from_str(&text)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = ron::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+}
+
+// Where the last finished run is saved
+#[derive(Resource)]
+pub struct ReplayFile(pub PathBuf);
+
+// The run being recorded, kept around once it is over
+#[derive(Resource, Deref, DerefMut)]
+pub struct Recording(pub Replay);
+
+// A replay being played back, its input replaces the player's until it runs out
+#[derive(Resource)]
+pub struct Playback {
+    replay: Replay,
+    span: usize,
+    tick: u32,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            span: 0,
+            tick: 0,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    fn next_input(&mut self) -> Option<PlayerInput> {
+        let (ticks, input) = *self.replay.inputs.get(self.span)?;
+        self.tick += 1;
+        if self.tick == ticks {
+            self.span += 1;
+            self.tick = 0;
+        }
+        Some(input)
+    }
+}
+
+fn start_recording(mut commands: Commands, seed: Res<RunSeed>, difficulty: Res<Difficulty>) {
+    commands.insert_resource(Recording(Replay::new(**seed, *difficulty)));
+}
+
+fn play_input(playback: Option<ResMut<Playback>>, mut input: ResMut<PlayerInput>) {
+    if let Some(mut playback) = playback {
+        *input = playback.next_input().unwrap_or_default();
+    }
+}
+
+fn record_input(recording: Option<ResMut<Recording>>, input: Res<PlayerInput>) {
+    if let Some(mut recording) = recording {
+        recording.push(*input);
+    }
+}
+
+fn finish_recording(
+    recording: Option<ResMut<Recording>>,
+    result: Res<RunResult>,
+    file: Option<Res<ReplayFile>>,
+    playback: Option<Res<Playback>>,
+) {
+    let Some(mut recording) = recording else {
+        return;
+    };
+    recording.score = result.score;
+    recording.wave = result.wave;
+
+    // Watching a replay doesn't overwrite it
+    if let (Some(file), None) = (file, playback) {
+        match recording.save(&file.0) {
+            Ok(()) => info!("Saved the run to {}", file.0.display()),
+            Err(error) => warn!("Couldn't save the run to {}: {error}", file.0.display()),
+        }
+    }
+}
+
+fn stop_playback(mut commands: Commands) {
+    commands.remove_resource::<Playback>();
+}
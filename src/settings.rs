@@ -0,0 +1,311 @@
+//! Player settings that aren't part of a run: how the window is shown, how loud the game is and
+//! which keys control the ship.
+//! They are kept in a file of the platform's config directory, from one launch to the next
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    window::{PresentMode, WindowMode, WindowResized},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{Controls, Difficulty},
+    FileError, WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+
+#[derive(Default)]
+pub struct SettingsPlugin {
+    pub settings: Settings,
+    // Where changes to the settings are saved, they only last until the game closes without it
+    pub file: Option<PathBuf>,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = &self.settings;
+        if let Some(file) = &self.file {
+            app.insert_resource(SettingsFile(file.clone()));
+        }
+
+        app.insert_resource(settings.difficulty)
+            .insert_resource(settings.display_mode)
+            .insert_resource(settings.resolution)
+            .insert_resource(settings.vsync)
+            .insert_resource(settings.master_volume)
+            .insert_resource(settings.music_volume)
+            .insert_resource(settings.effects_volume)
+            .insert_resource(settings.controls)
+            .add_system(apply_display_settings)
+            .add_system(scale_ui)
+            .add_system(save_settings);
+    }
+}
+
+// Everything stored in the settings file, a missing entry keeps its default value
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub display_mode: DisplayMode,
+    pub resolution: Resolution,
+    pub vsync: Vsync,
+    pub master_volume: MasterVolume,
+    pub music_volume: MusicVolume,
+    pub effects_volume: EffectsVolume,
+    pub controls: Controls,
+}
+
+impl Settings {
+    // The settings file in the platform's config directory, if it has one
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("cosmo_invaders").join("settings.ron"))
+    }
+
+    // Reads the settings from `path`, a missing file gives the defaults. They come with a note
+    // for the player when part of the file had to be thrown away
+    pub fn load(path: &Path) -> Result<(Self, Option<String>), FileError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok((Self::default(), None))
+            }
+            Err(error) => return Err(error.into()),
+        };
+        Ok(ron::from_str::<Settings>(&text)?.clamped())
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = ron::ser::to_string_pretty(self, default()).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    // Window matching the display settings
+    pub fn window(&self) -> WindowDescriptor {
+        WindowDescriptor {
+            width: self.resolution.width as f32,
+            height: self.resolution.height as f32,
+            mode: self.display_mode.window_mode(),
+            present_mode: self.vsync.present_mode(),
+            ..default()
+        }
+    }
+
+    // Brings hand edited values back within what the menus offer
+    fn clamped(mut self) -> (Self, Option<String>) {
+        let mut note = None;
+        self.master_volume.0 = self.master_volume.0.min(MAX_VOLUME);
+        self.music_volume.0 = self.music_volume.0.min(MAX_VOLUME);
+        self.effects_volume.0 = self.effects_volume.0.min(MAX_VOLUME);
+        self.resolution.width = self.resolution.width.max(MIN_RESOLUTION.width);
+        self.resolution.height = self.resolution.height.max(MIN_RESOLUTION.height);
+        if self.controls.has_conflicts() {
+            self.controls = Controls::default();
+            note = Some("a key is bound to two actions, using the default controls".into());
+        }
+        (self, note)
+    }
+}
+
+// Where the settings are saved
+#[derive(Resource)]
+pub struct SettingsFile(pub PathBuf);
+
+// The settings currently in use
+#[derive(SystemParam)]
+struct CurrentSettings<'w, 's> {
+    difficulty: Res<'w, Difficulty>,
+    display_mode: Res<'w, DisplayMode>,
+    resolution: Res<'w, Resolution>,
+    vsync: Res<'w, Vsync>,
+    master_volume: Res<'w, MasterVolume>,
+    music_volume: Res<'w, MusicVolume>,
+    effects_volume: Res<'w, EffectsVolume>,
+    controls: Res<'w, Controls>,
+    #[system_param(ignore)]
+    marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl CurrentSettings<'_, '_> {
+    fn settings(&self) -> Settings {
+        Settings {
+            difficulty: *self.difficulty,
+            display_mode: *self.display_mode,
+            resolution: *self.resolution,
+            vsync: *self.vsync,
+            master_volume: *self.master_volume,
+            music_volume: *self.music_volume,
+            effects_volume: *self.effects_volume,
+            controls: *self.controls,
+        }
+    }
+}
+
+#[derive(
+    Resource, Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::Fullscreen,
+    ];
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+// Size of the window, when it isn't fullscreen
+#[derive(Resource, Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    // All of them keep the 4:3 shape of the playfield
+    pub const ALL: [Resolution; 4] = [
+        Resolution::new(800, 600),
+        Resolution::new(1024, 768),
+        Resolution::new(1280, 960),
+        Resolution::new(1600, 1200),
+    ];
+
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+}
+
+// The smallest window still showing the whole playfield at its own size
+const MIN_RESOLUTION: Resolution = Resolution::new(WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32);
+
+impl Default for Resolution {
+    fn default() -> Self {
+        MIN_RESOLUTION
+    }
+}
+
+impl std::fmt::Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+#[derive(
+    Resource, Component, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Vsync {
+    #[default]
+    On,
+    Off,
+}
+
+impl Vsync {
+    pub fn present_mode(&self) -> PresentMode {
+        match self {
+            Vsync::On => PresentMode::AutoVsync,
+            Vsync::Off => PresentMode::AutoNoVsync,
+        }
+    }
+}
+
+// Volume levels go from 0, muted, up to `MAX_VOLUME`
+pub const MAX_VOLUME: u32 = 10;
+
+#[derive(Resource, Component, Clone, Copy, Debug, PartialEq, Eq, Deref, Serialize, Deserialize)]
+pub struct MasterVolume(pub u32);
+
+impl Default for MasterVolume {
+    fn default() -> Self {
+        Self(7)
+    }
+}
+
+#[derive(Resource, Component, Clone, Copy, Debug, PartialEq, Eq, Deref, Serialize, Deserialize)]
+pub struct MusicVolume(pub u32);
+
+impl Default for MusicVolume {
+    fn default() -> Self {
+        Self(5)
+    }
+}
+
+#[derive(Resource, Component, Clone, Copy, Debug, PartialEq, Eq, Deref, Serialize, Deserialize)]
+pub struct EffectsVolume(pub u32);
+
+impl Default for EffectsVolume {
+    fn default() -> Self {
+        Self(7)
+    }
+}
+
+// Keeps the primary window in line with the display settings
+fn apply_display_settings(
+    mut windows: ResMut<Windows>,
+    display_mode: Res<DisplayMode>,
+    resolution: Res<Resolution>,
+    vsync: Res<Vsync>,
+) {
+    let Some(window) = windows.get_primary_mut() else {
+        return;
+    };
+
+    if display_mode.is_changed() {
+        window.set_mode(display_mode.window_mode());
+    }
+    if resolution.is_changed() {
+        window.set_resolution(resolution.width as f32, resolution.height as f32);
+    }
+    if vsync.is_changed() {
+        window.set_present_mode(vsync.present_mode());
+    }
+}
+
+// The menus and the HUD grow with the window, like the playfield does
+fn scale_ui(mut resized_events: EventReader<WindowResized>, mut ui_scale: ResMut<UiScale>) {
+    if let Some(resized) = resized_events.iter().last() {
+        ui_scale.scale = (resized.width / WINDOW_WIDTH).min(resized.height / WINDOW_HEIGHT) as f64;
+    }
+}
+
+// Writes the settings back to their file every time one of them changes
+fn save_settings(
+    file: Option<Res<SettingsFile>>,
+    current: CurrentSettings,
+    mut saved: Local<Option<Settings>>,
+) {
+    let settings = current.settings();
+    // The first run only takes note of the settings loaded at startup
+    let Some(saved) = saved.replace(settings.clone()) else {
+        return;
+    };
+    if saved == settings {
+        return;
+    }
+
+    if let Some(SettingsFile(path)) = file.as_deref() {
+        if let Err(error) = settings.save(path) {
+            warn!("Couldn't save the settings to {}: {error}", path.display());
+        }
+    }
+}
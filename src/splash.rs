@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::{despawn_screen, GameState};
+
+pub struct SplashPlugin;
+
+impl Plugin for SplashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Splash).with_system(splash_setup))
+            .add_system_set(SystemSet::on_update(GameState::Splash).with_system(countdown))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Splash).with_system(despawn_screen::<OnSplashScreen>),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OnSplashScreen;
+
+#[derive(Resource, Deref, DerefMut)]
+struct SplashTimer(Timer);
+
+fn splash_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let icon = asset_server.load("branding/logo.png");
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            OnSplashScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style {
+                    size: Size::new(Val::Px(200.0), Val::Auto),
+                    ..default()
+                },
+                image: icon.into(),
+                ..default()
+            });
+        });
+
+    commands.insert_resource(SplashTimer(Timer::from_seconds(1.0, TimerMode::Once)));
+}
+
+fn countdown(
+    mut game_state: ResMut<State<GameState>>,
+    time: Res<Time>,
+    mut timer: ResMut<SplashTimer>,
+) {
+    if timer.tick(time.delta()).finished() {
+        game_state.set(GameState::Menu).unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+// Shared harness for the integration tests: a headless game driven one simulation tick at a time
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use cosmo_invaders::{
+    game::{EnemyFire, Player, SimulationClock},
+    headless, GameState,
+};
+
+pub const TEST_SEED: u64 = 1234;
+
+// Long enough for the weapon cooldown of a new game to run out
+pub const WARM_UP_TICKS: u32 = 10;
+
+pub struct TestGame {
+    pub app: App,
+}
+
+impl TestGame {
+    // A game where the bugs never shoot back, so the tests only see what they cause themselves
+    pub fn new() -> Self {
+        let mut game = Self::with_seed(TEST_SEED);
+        game.app.insert_resource(EnemyFire {
+            shots_per_second: 0.0,
+        });
+        game
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            app: headless::app(Some(seed)),
+        }
+    }
+
+    // Holds `key` down from the next tick on, until it is released
+    pub fn press(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<Input<KeyCode>>().press(key);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<Input<KeyCode>>().release(key);
+    }
+
+    pub fn tick(&mut self) {
+        self.app
+            .world
+            .resource_mut::<SimulationClock>()
+            .queue_ticks(1);
+        self.app.update();
+        // What the `InputPlugin` would do at the start of the next frame
+        self.app.world.resource_mut::<Input<KeyCode>>().clear();
+    }
+
+    // Quits the current run to the menu and starts a new one in the same app
+    pub fn restart(&mut self) {
+        let mut state = self.app.world.resource_mut::<State<GameState>>();
+        state.set(GameState::Menu).unwrap();
+        self.app.update();
+        let mut state = self.app.world.resource_mut::<State<GameState>>();
+        state.set(GameState::Game).unwrap();
+        self.app.update();
+    }
+
+    // Makes the next entities come out in another order than in a fresh app, as the menus or a
+    // previous run would
+    pub fn scramble_entity_ids(&mut self) {
+        let world = &mut self.app.world;
+        let dummies: Vec<Entity> = (0..500).map(|_| world.spawn_empty().id()).collect();
+        for entity in dummies {
+            world.despawn(entity);
+        }
+    }
+
+    pub fn ticks(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.tick();
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app
+            .world
+            .resource::<State<GameState>>()
+            .current()
+            .clone()
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world.resource::<R>()
+    }
+
+    pub fn player_position(&mut self) -> Vec3 {
+        self.positions::<Player>()[0]
+    }
+
+    // Positions of every entity with a `T` component
+    pub fn positions<T: Component>(&mut self) -> Vec<Vec3> {
+        let mut query = self.app.world.query_filtered::<&Transform, With<T>>();
+        query
+            .iter(&self.app.world)
+            .map(|trans| trans.translation)
+            .collect()
+    }
+
+    pub fn entities<T: Component>(&mut self) -> Vec<Entity> {
+        let mut query = self.app.world.query_filtered::<Entity, With<T>>();
+        query.iter(&self.app.world).collect()
+    }
+
+    pub fn move_to(&mut self, entity: Entity, position: Vec3) {
+        self.app
+            .world
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation = position;
+    }
+}
@@ -0,0 +1,189 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{TestGame, WARM_UP_TICKS};
+use cosmo_invaders::game::{
+    Bug, BugKind, Collider, CollisionLayer, Laser, Player, PowerUpCapsule, Score, Wave, SHOT_SIZE,
+};
+
+fn bugs(game: &mut TestGame) -> Vec<(Entity, BugKind, u32, Vec3)> {
+    let mut query = game.app.world.query::<(Entity, &Bug, &Transform)>();
+    query
+        .iter(&game.app.world)
+        .map(|(entity, bug, trans)| (entity, bug.kind, bug.health, trans.translation))
+        .collect()
+}
+
+// Fires a laser and puts it right onto `target`
+fn shoot_at(game: &mut TestGame, target: Entity) {
+    game.press(KeyCode::Space);
+    game.tick();
+    game.release(KeyCode::Space);
+
+    let laser = game.entities::<Laser>()[0];
+    let position = game.app.world.get::<Transform>(target).unwrap().translation;
+    game.move_to(laser, position);
+    game.tick();
+}
+
+#[test]
+fn formation_marches_in_lockstep() {
+    let mut game = TestGame::new();
+    let before = bugs(&mut game);
+
+    game.ticks(10);
+    let after = bugs(&mut game);
+
+    let shift = after[0].3 - before[0].3;
+    assert_ne!(shift, Vec3::ZERO);
+    for ((_, _, _, start), (_, _, _, end)) in before.iter().zip(&after) {
+        assert!((*end - *start - shift).length() < 0.001);
+    }
+}
+
+#[test]
+fn formation_steps_down_over_time() {
+    let mut game = TestGame::new();
+    let before = bugs(&mut game);
+
+    game.ticks(600);
+    let after = bugs(&mut game);
+
+    assert_eq!(before.len(), after.len());
+    for ((_, _, _, start), (_, _, _, end)) in before.iter().zip(&after) {
+        assert!(end.y < start.y);
+    }
+}
+
+#[test]
+fn bug_dies_when_hit() {
+    let mut game = TestGame::new();
+    game.ticks(WARM_UP_TICKS);
+    let (target, kind, _, _) = bugs(&mut game)
+        .into_iter()
+        .find(|(_, kind, _, _)| *kind == BugKind::Octopus)
+        .unwrap();
+    let count = bugs(&mut game).len();
+
+    shoot_at(&mut game, target);
+
+    assert!(game.app.world.get_entity(target).is_none());
+    assert_eq!(bugs(&mut game).len(), count - 1);
+    assert!(game.entities::<Laser>().is_empty());
+    assert_eq!(**game.resource::<Score>(), kind.points());
+}
+
+#[test]
+fn squid_takes_two_hits() {
+    let mut game = TestGame::new();
+    game.ticks(WARM_UP_TICKS);
+    let (target, kind, health, _) = bugs(&mut game)
+        .into_iter()
+        .find(|(_, kind, _, _)| *kind == BugKind::Squid)
+        .unwrap();
+    assert_eq!(health, kind.hit_points());
+    assert_eq!(health, 2);
+
+    shoot_at(&mut game, target);
+    assert_eq!(game.app.world.get::<Bug>(target).unwrap().health, 1);
+    assert_eq!(**game.resource::<Score>(), 0);
+
+    game.ticks(WARM_UP_TICKS);
+    shoot_at(&mut game, target);
+    assert!(game.app.world.get_entity(target).is_none());
+    assert_eq!(**game.resource::<Score>(), kind.points());
+}
+
+#[test]
+fn piercing_laser_hits_each_bug_once() {
+    let mut game = TestGame::new();
+    let (target, _, _, position) = bugs(&mut game)
+        .into_iter()
+        .find(|(_, kind, _, _)| *kind == BugKind::Squid)
+        .unwrap();
+    let player = game.entities::<Player>()[0];
+    let laser = game
+        .app
+        .world
+        .spawn((
+            Transform::from_translation(position),
+            Laser {
+                piercing: true,
+                ..Laser::new(player)
+            },
+            Collider::new(CollisionLayer::PlayerShot, SHOT_SIZE),
+        ))
+        .id();
+
+    // Held over the squid for as long as it takes a laser to cross it
+    for _ in 0..WARM_UP_TICKS {
+        let position = game.app.world.get::<Transform>(target).unwrap().translation;
+        game.move_to(laser, position);
+        game.tick();
+    }
+
+    assert_eq!(game.app.world.get::<Bug>(target).unwrap().health, 1);
+    assert!(game.app.world.get_entity(laser).is_some());
+}
+
+#[test]
+fn cleared_formation_brings_the_next_wave() {
+    let mut game = TestGame::new();
+    let count = bugs(&mut game).len();
+    let top = bugs(&mut game)
+        .iter()
+        .map(|(_, _, _, position)| position.y)
+        .fold(f32::MIN, f32::max);
+
+    for (entity, _, _, _) in bugs(&mut game) {
+        game.app.world.despawn(entity);
+    }
+    game.ticks(60);
+    assert!(bugs(&mut game).is_empty());
+    assert_eq!(**game.resource::<Wave>(), 1);
+
+    game.ticks(90);
+    assert_eq!(**game.resource::<Wave>(), 2);
+    let next = bugs(&mut game);
+    assert_eq!(next.len(), count);
+    // Later waves start closer to the player
+    assert!(next.iter().all(|(_, _, _, position)| position.y < top));
+}
+
+// Puts a laser onto every bug going down in one hit and returns the power-ups they drop, once
+// they all got hit during the same tick
+fn volley(game: &mut TestGame) -> Vec<(String, Vec3)> {
+    let player = game.entities::<Player>()[0];
+    for (_, _, health, position) in bugs(game) {
+        if health == 1 {
+            game.app.world.spawn((
+                Transform::from_translation(position),
+                Laser::new(player),
+                Collider::new(CollisionLayer::PlayerShot, SHOT_SIZE),
+            ));
+        }
+    }
+    game.tick();
+
+    let mut query = game.app.world.query::<(&PowerUpCapsule, &Transform)>();
+    let mut drops: Vec<_> = query
+        .iter(&game.app.world)
+        .map(|(PowerUpCapsule(kind), trans)| (format!("{kind:?}"), trans.translation))
+        .collect();
+    drops.sort_by(|a, b| a.1.x.total_cmp(&b.1.x));
+    drops
+}
+
+#[test]
+fn hits_do_not_depend_on_entity_ids() {
+    let mut fresh = TestGame::new();
+    fresh.restart();
+
+    let mut reused = TestGame::new();
+    reused.scramble_entity_ids();
+    reused.restart();
+
+    let drops = volley(&mut fresh);
+    assert!(!drops.is_empty());
+    assert_eq!(volley(&mut reused), drops);
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{TestGame, WARM_UP_TICKS};
+use cosmo_invaders::{
+    game::{Controls, Laser},
+    WINDOW_WIDTH,
+};
+
+#[test]
+fn player_moves_with_the_arrow_keys() {
+    let mut game = TestGame::new();
+    let start = game.player_position();
+
+    game.press(KeyCode::Left);
+    game.ticks(10);
+    let left = game.player_position();
+    assert!(left.x < start.x);
+
+    game.release(KeyCode::Left);
+    game.press(KeyCode::Right);
+    game.ticks(20);
+    assert!(game.player_position().x > left.x);
+}
+
+#[test]
+fn player_follows_rebound_keys() {
+    let mut game = TestGame::new();
+    game.app.insert_resource(Controls {
+        left: KeyCode::A,
+        right: KeyCode::D,
+        fire: KeyCode::Z,
+    });
+    game.ticks(WARM_UP_TICKS);
+    let start = game.player_position();
+
+    // The default keys do nothing anymore
+    game.press(KeyCode::Left);
+    game.press(KeyCode::Space);
+    game.ticks(10);
+    assert_eq!(game.player_position(), start);
+    assert!(game.entities::<Laser>().is_empty());
+    game.release(KeyCode::Left);
+    game.release(KeyCode::Space);
+
+    game.press(KeyCode::A);
+    game.press(KeyCode::Z);
+    game.ticks(10);
+    assert!(game.player_position().x < start.x);
+    assert_eq!(game.entities::<Laser>().len(), 1);
+}
+
+#[test]
+fn player_stops_at_the_edges() {
+    let mut game = TestGame::new();
+
+    game.press(KeyCode::Left);
+    for _ in 0..300 {
+        game.tick();
+        assert!(game.player_position().x >= -(WINDOW_WIDTH / 2.5));
+    }
+    assert_eq!(game.player_position().x, -(WINDOW_WIDTH / 2.5));
+
+    game.release(KeyCode::Left);
+    game.press(KeyCode::Right);
+    for _ in 0..300 {
+        game.tick();
+        assert!(game.player_position().x <= WINDOW_WIDTH / 2.5);
+    }
+    assert_eq!(game.player_position().x, WINDOW_WIDTH / 2.5);
+}
+
+#[test]
+fn player_slows_down_once_released() {
+    let mut game = TestGame::new();
+
+    game.press(KeyCode::Right);
+    game.ticks(5);
+    game.release(KeyCode::Right);
+    game.ticks(60);
+
+    let resting = game.player_position();
+    game.tick();
+    assert!((game.player_position().x - resting.x).abs() < 0.01);
+}
+
+#[test]
+fn laser_spawns_above_the_player() {
+    let mut game = TestGame::new();
+    game.ticks(WARM_UP_TICKS);
+
+    game.press(KeyCode::Space);
+    game.tick();
+
+    let player = game.player_position();
+    assert_eq!(
+        game.positions::<Laser>(),
+        vec![player + Vec3::new(0.0, 24.0, 0.0)]
+    );
+}
+
+#[test]
+fn laser_flies_up_and_leaves_the_screen() {
+    let mut game = TestGame::new();
+    game.ticks(WARM_UP_TICKS);
+    game.press(KeyCode::Space);
+    game.tick();
+
+    // Beside the formation and the shields, so nothing stops it
+    let laser = game.entities::<Laser>()[0];
+    game.move_to(laser, Vec3::new(WINDOW_WIDTH / 2.0 - 12.0, 0.0, 0.0));
+    game.tick();
+    assert!(game.positions::<Laser>()[0].y > 0.0);
+
+    game.ticks(300);
+    assert!(game.entities::<Laser>().is_empty());
+}
+
+#[test]
+fn single_shot_allows_one_laser_at_a_time() {
+    let mut game = TestGame::new();
+    game.ticks(WARM_UP_TICKS);
+
+    for _ in 0..3 {
+        game.press(KeyCode::Space);
+        game.tick();
+        game.release(KeyCode::Space);
+        game.ticks(WARM_UP_TICKS);
+    }
+
+    assert_eq!(game.entities::<Laser>().len(), 1);
+}
+
+#[test]
+fn lasers_of_other_ships_do_not_count() {
+    let mut game = TestGame::new();
+    game.ticks(WARM_UP_TICKS);
+    let other_ship = game.app.world.spawn_empty().id();
+    game.app
+        .world
+        .spawn((Transform::default(), Laser::new(other_ship)));
+
+    game.press(KeyCode::Space);
+    game.tick();
+
+    assert_eq!(game.entities::<Laser>().len(), 2);
+}
+
+#[test]
+fn holding_fire_shoots_only_once() {
+    let mut game = TestGame::new();
+    game.ticks(WARM_UP_TICKS);
+    game.press(KeyCode::Space);
+    game.tick();
+    let laser = game.entities::<Laser>()[0];
+
+    // Get rid of the first laser, the key is still held down
+    game.app.world.despawn(laser);
+    game.ticks(WARM_UP_TICKS);
+
+    assert!(game.entities::<Laser>().is_empty());
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use bevy::prelude::*;
+use common::TestGame;
+use cosmo_invaders::{
+    game::{Difficulty, PlayerInput},
+    headless,
+    replay::{Recording, Replay},
+    GameState,
+};
+
+#[test]
+fn replay_survives_a_round_trip_to_disk() {
+    let mut replay = Replay::new(99, Difficulty::Hard);
+    for tick in 0..100 {
+        replay.push(PlayerInput {
+            left: tick < 40,
+            fire: tick % 25 == 0,
+            ..default()
+        });
+    }
+    replay.score = 120;
+    assert_eq!(replay.ticks(), 100);
+
+    let path = std::env::temp_dir().join("cosmo_invaders_round_trip.ron");
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, replay);
+}
+
+#[test]
+fn broken_replay_fails_to_load() {
+    let path = std::env::temp_dir().join("cosmo_invaders_broken.ron");
+    std::fs::write(&path, "(seed: \"nope\")").unwrap();
+    let loaded = Replay::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(loaded.is_err());
+}
+
+// Plays until game over, sweeping from side to side and firing all the time
+fn play_to_the_end(game: &mut TestGame) -> Replay {
+    let mut tick = 0;
+    while game.state() == GameState::Game {
+        assert!(tick < 20_000, "the run never ended");
+        let (key, other) = if tick / 90 % 2 == 0 {
+            (KeyCode::Left, KeyCode::Right)
+        } else {
+            (KeyCode::Right, KeyCode::Left)
+        };
+        game.release(other);
+        game.press(key);
+        if tick % 20 == 0 {
+            game.press(KeyCode::Space);
+        } else if tick % 20 == 10 {
+            game.release(KeyCode::Space);
+        }
+        game.tick();
+        tick += 1;
+    }
+    game.resource::<Recording>().0.clone()
+}
+
+fn assert_replays_the_same(replay: Replay) {
+    assert!(replay.score > 0);
+
+    let report = headless::play_replay(replay.clone());
+
+    assert_eq!(report.state, GameState::GameOver);
+    assert_eq!(report.ticks, u64::from(replay.ticks()));
+    assert_eq!(report.score, replay.score);
+    assert_eq!(report.wave, replay.wave);
+}
+
+#[test]
+fn replay_plays_the_run_again() {
+    let mut game = TestGame::with_seed(7);
+    assert_replays_the_same(play_to_the_end(&mut game));
+}
+
+#[test]
+fn replay_does_not_depend_on_earlier_entities() {
+    let mut game = TestGame::with_seed(7);
+    // Two lasers on screen make kills during the same tick more likely
+    game.app.insert_resource(Difficulty::Easy);
+
+    game.scramble_entity_ids();
+    game.restart();
+
+    let replay = play_to_the_end(&mut game);
+    assert_eq!(replay.difficulty, Difficulty::Easy);
+    assert_replays_the_same(replay);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use bevy::prelude::*;
+use common::TestGame;
+use cosmo_invaders::{
+    game::{Bug, BugLaser, DeathCause, Lives, Player, RunResult, Score},
+    GameState,
+};
+
+// Puts every bug on the player's row, which counts as an invasion
+fn land_the_bugs(game: &mut TestGame) {
+    let player = game.player_position();
+    for bug in game.entities::<Bug>() {
+        let x = game.app.world.get::<Transform>(bug).unwrap().translation.x;
+        game.move_to(bug, Vec3::new(x, player.y, 0.0));
+    }
+}
+
+#[test]
+fn escape_freezes_the_game() {
+    let mut game = TestGame::new();
+    game.ticks(5);
+
+    game.press(KeyCode::Escape);
+    game.tick();
+    assert_eq!(game.state(), GameState::Paused);
+
+    let bugs = game.positions::<Bug>();
+    game.press(KeyCode::Left);
+    let player = game.player_position();
+    game.ticks(30);
+    assert_eq!(game.positions::<Bug>(), bugs);
+    assert_eq!(game.player_position(), player);
+}
+
+#[test]
+fn invasion_costs_a_life() {
+    let mut game = TestGame::new();
+
+    land_the_bugs(&mut game);
+    game.tick();
+
+    assert_eq!(**game.resource::<Lives>(), 2);
+    assert_eq!(game.state(), GameState::Game);
+    // The formation starts over from the top
+    let player = game.player_position();
+    assert!(game
+        .positions::<Bug>()
+        .iter()
+        .all(|position| position.y - player.y > 100.0));
+}
+
+#[test]
+fn losing_the_last_life_ends_the_run() {
+    let mut game = TestGame::new();
+    game.app.insert_resource(Lives(1));
+
+    land_the_bugs(&mut game);
+    game.ticks(2);
+
+    assert_eq!(game.state(), GameState::GameOver);
+    let result = game.resource::<RunResult>();
+    assert_eq!(result.cause, DeathCause::Invaded);
+    assert_eq!(result.wave, 1);
+    // The game screen is cleaned up on the way out
+    assert!(game.entities::<Player>().is_empty());
+    assert!(game.entities::<Bug>().is_empty());
+}
+
+#[test]
+fn bugs_shoot_back() {
+    let mut game = TestGame::with_seed(common::TEST_SEED);
+
+    let mut fired = false;
+    for _ in 0..600 {
+        game.tick();
+        fired |= !game.entities::<BugLaser>().is_empty();
+    }
+
+    assert!(fired);
+}
+
+#[test]
+fn same_seed_plays_the_same_run() {
+    let play = || {
+        let mut game = TestGame::with_seed(42);
+        for round in 0..20 {
+            let key = if round % 2 == 0 {
+                KeyCode::Left
+            } else {
+                KeyCode::Right
+            };
+            game.press(key);
+            game.press(KeyCode::Space);
+            game.ticks(30);
+            game.release(key);
+            game.release(KeyCode::Space);
+        }
+        (
+            **game.resource::<Score>(),
+            **game.resource::<Lives>(),
+            game.positions::<Bug>(),
+            game.positions::<BugLaser>(),
+        )
+    };
+
+    let run = play();
+    // Something actually happened during the run
+    assert!(run.0 > 0);
+    assert_eq!(run, play());
+}
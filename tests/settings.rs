@@ -0,0 +1,102 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::KeyCode;
+use cosmo_invaders::{
+    game::{ControlAction, Controls, Difficulty},
+    settings::{DisplayMode, MasterVolume, MusicVolume, Resolution, Settings, Vsync, MAX_VOLUME},
+};
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join("cosmo_invaders_tests").join(name)
+}
+
+#[test]
+fn settings_survive_a_round_trip_to_disk() {
+    let path = temp_file("round_trip.ron");
+    let settings = Settings {
+        difficulty: Difficulty::Hard,
+        display_mode: DisplayMode::Borderless,
+        resolution: Resolution::new(1280, 960),
+        vsync: Vsync::Off,
+        music_volume: MusicVolume(2),
+        controls: Controls {
+            fire: KeyCode::Z,
+            ..Controls::default()
+        },
+        ..Settings::default()
+    };
+
+    settings.save(&path).unwrap();
+    let (loaded, note) = Settings::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, settings);
+    assert_eq!(note, None);
+}
+
+#[test]
+fn missing_file_gives_the_defaults() {
+    assert_eq!(
+        Settings::load(&temp_file("does_not_exist.ron")).unwrap(),
+        (Settings::default(), None)
+    );
+}
+
+#[test]
+fn broken_file_fails_to_load() {
+    let path = temp_file("broken.ron");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "(difficulty: Impossible, vsync: ").unwrap();
+
+    let loaded = Settings::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    // Tells where the file goes wrong
+    let error = loaded.unwrap_err().to_string();
+    assert!(error.contains("line 1, column"), "{error}");
+}
+
+#[test]
+fn missing_entries_keep_their_defaults() {
+    let path = temp_file("partial.ron");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "(difficulty: Easy, master_volume: (99))").unwrap();
+
+    let (loaded, _) = Settings::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.difficulty, Difficulty::Easy);
+    assert_eq!(loaded.master_volume, MasterVolume(MAX_VOLUME));
+    assert_eq!(loaded.resolution, Resolution::default());
+}
+
+#[test]
+fn a_key_can_only_be_bound_once() {
+    let mut controls = Controls::default();
+
+    assert_eq!(
+        controls.bind(ControlAction::Fire, KeyCode::Left),
+        Err(ControlAction::Left)
+    );
+    assert_eq!(controls, Controls::default());
+
+    assert_eq!(controls.bind(ControlAction::Fire, KeyCode::Space), Ok(()));
+    assert_eq!(controls.bind(ControlAction::Left, KeyCode::A), Ok(()));
+    assert_eq!(controls.key(ControlAction::Left), KeyCode::A);
+    assert_eq!(controls.action(KeyCode::Left), None);
+}
+
+#[test]
+fn conflicting_controls_give_the_defaults() {
+    let path = temp_file("conflicts.ron");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "(vsync: Off, controls: (left: A, right: A))").unwrap();
+
+    let (loaded, note) = Settings::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.vsync, Vsync::Off);
+    assert_eq!(loaded.controls, Controls::default());
+    // The player gets told why their bindings are gone
+    assert!(note.is_some());
+}